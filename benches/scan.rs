@@ -0,0 +1,153 @@
+//! compares the former approach (spawning git via sh for every branch of every
+//! repo) with a full scan by oper-delta on a synthetic workspace managed by repo;
+//! run with `cargo bench --bench scan`
+//!
+//! The workspace is built with git2 directly as the test helpers of the binary
+//! (`model::create_synthetic_repo`) aren't accessible from here.
+
+use git2::{Oid, Repository, Signature};
+use std::path::Path;
+use std::process::Command;
+use std::time::Instant;
+
+const NR_OF_PROJECTS: usize = 200;
+const NR_OF_SHARED_COMMITS: usize = 200;
+
+fn main() {
+    let workspace = std::env::temp_dir().join("oper-delta-bench");
+    let _ = std::fs::remove_dir_all(&workspace);
+    let project_paths = create_workspace(&workspace);
+
+    let start = Instant::now();
+    let shell_results = project_paths
+        .iter()
+        .map(|path| distances_by_shell(&workspace.join(path)))
+        .collect::<Vec<_>>();
+    let shell_duration = start.elapsed();
+
+    let start = Instant::now();
+    let output = Command::new(env!("CARGO_BIN_EXE_oper-delta"))
+        .arg("--cwd")
+        .arg(&workspace)
+        .args(["--format", "json", "release"])
+        .output()
+        .unwrap();
+    let oper_delta_duration = start.elapsed();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let oper_delta_results = project_paths
+        .iter()
+        .map(|path| {
+            let repo = report["repos"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|repo| repo["path"] == path.as_str())
+                .unwrap();
+            let branch = &repo["branches"][0];
+            (
+                branch["distance_head_to_merge_base"].as_u64().unwrap() as u32,
+                branch["distance_target_to_merge_base"].as_u64().unwrap() as u32,
+                branch["delta"] == "NotConsolidatedButFastForwardable",
+            )
+        })
+        .collect::<Vec<_>>();
+
+    println!(
+        "{} projects: shell/git {:?}, oper-delta {:?}",
+        NR_OF_PROJECTS, shell_duration, oper_delta_duration
+    );
+    assert_eq!(shell_results, oper_delta_results);
+    let _ = std::fs::remove_dir_all(&workspace);
+}
+
+/// creates nested projects (listed in `.repo/project.list`) whose HEAD and
+/// "release" branch diverge by a varying number of commits
+fn create_workspace(workspace: &Path) -> Vec<String> {
+    let project_paths = (0..NR_OF_PROJECTS)
+        .map(|n| format!("platform/group{}/project{}", n / 20, n))
+        .collect::<Vec<_>>();
+    for (n, path) in project_paths.iter().enumerate() {
+        create_repo(&workspace.join(path), n % 10, (n % 3) * 10);
+    }
+
+    std::fs::create_dir_all(workspace.join(".repo")).unwrap();
+    std::fs::write(
+        workspace.join(".repo").join("project.list"),
+        project_paths.join("\n") + "\n",
+    )
+    .unwrap();
+    project_paths
+}
+
+fn create_repo(path: &Path, nr_of_head_commits: usize, nr_of_target_commits: usize) {
+    let git_repo = Repository::init(path).unwrap();
+    let sig = Signature::now("oper-delta", "oper-delta@example.com").unwrap();
+    let commit = |parent: Option<Oid>, content: String| {
+        let mut tree_builder = git_repo.treebuilder(None).unwrap();
+        let blob = git_repo.blob(content.as_bytes()).unwrap();
+        tree_builder.insert("file", blob, 0o100_644).unwrap();
+        let tree = git_repo.find_tree(tree_builder.write().unwrap()).unwrap();
+        let parents = parent
+            .map(|id| git_repo.find_commit(id).unwrap())
+            .into_iter()
+            .collect::<Vec<_>>();
+        let parents = parents.iter().collect::<Vec<_>>();
+        git_repo
+            .commit(None, &sig, &sig, &content, &tree, &parents)
+            .unwrap()
+    };
+
+    let mut merge_base = None;
+    for n in 0..NR_OF_SHARED_COMMITS {
+        merge_base = Some(commit(merge_base, format!("shared {}", n)));
+    }
+    let mut head = merge_base;
+    for n in 0..nr_of_head_commits {
+        head = Some(commit(head, format!("head {}", n)));
+    }
+    let mut target = merge_base;
+    for n in 0..nr_of_target_commits {
+        target = Some(commit(target, format!("target {}", n)));
+    }
+
+    git_repo
+        .reference("refs/heads/main", head.unwrap(), true, "")
+        .unwrap();
+    git_repo
+        .reference("refs/heads/release", target.unwrap(), true, "")
+        .unwrap();
+    git_repo.set_head("refs/heads/main").unwrap();
+}
+
+/// distances of HEAD and "release" to their merge-base and whether "release" can be
+/// fast-forwarded to HEAD (but not already equal to it), like oper-delta did before libgit2
+fn distances_by_shell(path: &Path) -> (u32, u32, bool) {
+    let count = |range: &str| {
+        let output = Command::new("sh")
+            .current_dir(path)
+            .arg("-c")
+            .arg(format!("git rev-list --count {}", range))
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse::<u32>()
+            .unwrap()
+    };
+    let head = count("`git merge-base HEAD release`..HEAD");
+    let target = count("`git merge-base HEAD release`..release");
+    let fast_forwardable = head > 0
+        && Command::new("git")
+            .current_dir(path)
+            .args(["merge-base", "--is-ancestor", "release", "HEAD"])
+            .status()
+            .unwrap()
+            .success();
+    (head, target, fast_forwardable)
+}
@@ -0,0 +1,76 @@
+use crate::model::RepoBranchDeltas;
+use crate::utils::{execute_on_repo_and_capture, CommandContext};
+use rayon::prelude::*;
+use std::process::Output;
+
+/// the result of a custom command executed on a single repo
+pub struct BatchResult {
+    pub repo: String,
+    pub output: Result<Output, String>,
+}
+
+impl BatchResult {
+    pub fn success(&self) -> bool {
+        self.output.as_ref().is_ok_and(|o| o.status.success())
+    }
+}
+
+/// executes a custom command on all given repos in parallel (using rayon's
+/// global pool); placeholders refering to a target branch use the branch with
+/// the given index, `{}` is replaced with HEAD. `on_result` is called as soon as
+/// the command finished on a repo.
+pub fn run<F>(
+    executable: &str,
+    args: &str,
+    repos: &[RepoBranchDeltas],
+    branch_index: Option<usize>,
+    on_result: F,
+) -> Vec<BatchResult>
+where
+    F: Fn(&BatchResult) + Sync,
+{
+    repos
+        .par_iter()
+        .map(|repo_deltas| {
+            let context = CommandContext {
+                repo_deltas,
+                branch: branch_index.and_then(|n| repo_deltas.deltas.get(n)),
+                commit: None,
+            };
+            let result = BatchResult {
+                repo: repo_deltas.repo.rel_path.clone(),
+                output: execute_on_repo_and_capture(executable, args, &context)
+                    .map_err(|e| e.to_string()),
+            };
+            on_result(&result);
+            result
+        })
+        .collect()
+}
+
+#[test]
+fn test_run() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    //an absolute path replaces the one of the test workspace
+    let tmp = std::env::temp_dir().to_string_lossy().to_string();
+    let repos = vec![
+        RepoBranchDeltas::with_deltas("missing", vec![]),
+        RepoBranchDeltas::with_deltas(&tmp, vec![]),
+    ];
+
+    let count = AtomicUsize::new(0);
+    let results = run("echo", "{repo}", &repos, None, |_| {
+        count.fetch_add(1, Ordering::SeqCst);
+    });
+
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+    //the command can't be started in a folder which doesn't exist
+    assert!(results[0].output.is_err());
+    assert!(!results[0].success());
+    assert!(results[1].success());
+    assert_eq!(
+        results[1].output.as_ref().unwrap().stdout,
+        format!("{}\n", tmp).as_bytes()
+    );
+}
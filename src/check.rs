@@ -0,0 +1,156 @@
+use crate::model::{branch_names, Delta, RepoBranchDeltas};
+use anyhow::Result;
+use std::io::Write;
+
+/// default states which let a check fail
+pub const DEFAULT_FAIL_ON: [Delta; 3] = [
+    Delta::NotConsolidatedButFastForwardable,
+    Delta::NotConsolidated,
+    Delta::BranchNotFound,
+];
+
+/// writes a compact summary of the model and lists all repos having
+/// one of the given `fail_on` states on any branch or which couldn't
+/// been scanned; returns false if at least one such repo has been found
+/// or if there isn't any repo at all
+pub fn run(model: &[RepoBranchDeltas], fail_on: &[Delta], out: &mut dyn Write) -> Result<bool> {
+    if model.is_empty() {
+        writeln!(out, "Check failed: no repos have been scanned")?;
+        return Ok(false);
+    }
+
+    for (n, branch) in branch_names(model).iter().enumerate() {
+        let counts = Delta::ALL
+            .iter()
+            .map(|delta| {
+                (
+                    delta,
+                    model
+                        .iter()
+                        .filter(|r| r.deltas.get(n).is_some_and(|b| &b.delta == delta))
+                        .count(),
+                )
+            })
+            .filter(|(_, count)| *count > 0)
+            .map(|(delta, count)| format!("{} {}", count, delta))
+            .collect::<Vec<_>>();
+        writeln!(out, "{}: {}", branch, counts.join(", "))?;
+    }
+
+    let mut nr_of_failed_repos = 0;
+    for repo in model {
+        if let Some(error) = &repo.error {
+            nr_of_failed_repos += 1;
+            writeln!(out, "  {}: {}", repo.repo.rel_path, error)?;
+            continue;
+        }
+
+        let failed_branches = repo
+            .deltas
+            .iter()
+            .filter(|b| fail_on.contains(&b.delta))
+            .map(|b| format!("{} {}", b.branch_name, b.delta))
+            .collect::<Vec<_>>();
+        if !failed_branches.is_empty() {
+            nr_of_failed_repos += 1;
+            writeln!(
+                out,
+                "  {}: {}",
+                repo.repo.rel_path,
+                failed_branches.join(", ")
+            )?;
+        }
+    }
+
+    if nr_of_failed_repos == 0 {
+        writeln!(out, "Check passed: {} repos checked", model.len())?;
+    } else {
+        writeln!(
+            out,
+            "Check failed: {} of {} repos are in state {} or couldn't be scanned",
+            nr_of_failed_repos,
+            model.len(),
+            fail_on
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()
+                .join(" or ")
+        )?;
+    }
+
+    Ok(nr_of_failed_repos == 0)
+}
+
+#[cfg(test)]
+fn test_model(deltas: &[(&str, Delta)]) -> Vec<RepoBranchDeltas> {
+    use crate::model::BranchDelta;
+
+    deltas
+        .iter()
+        .map(|(path, delta)| {
+            RepoBranchDeltas::with_deltas(
+                path,
+                vec![BranchDelta::with_delta("release", delta.clone())],
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_check_passed() {
+    let model = test_model(&[
+        ("a", Delta::ConsolidatedBySameCommit),
+        ("b", Delta::ConsolidatedBySameCommit),
+        ("c", Delta::BranchNotFound),
+    ]);
+    let mut out = Vec::<u8>::new();
+
+    assert!(run(&model, &[Delta::NotConsolidated], &mut out).unwrap());
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "release: 2 ConsolidatedBySameCommit, 1 BranchNotFound\nCheck passed: 3 repos checked\n"
+    );
+}
+
+#[test]
+fn test_check_failed() {
+    let model = test_model(&[
+        ("a", Delta::ConsolidatedBySameCommit),
+        ("b", Delta::NotConsolidated),
+    ]);
+    let mut out = Vec::<u8>::new();
+
+    assert!(!run(&model, &DEFAULT_FAIL_ON, &mut out).unwrap());
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .contains("  b: release NotConsolidated\nCheck failed: 1 of 2 repos"));
+}
+
+#[test]
+fn test_check_failed_by_repo_error() {
+    use crate::model::RepoError;
+
+    let mut model = test_model(&[("a", Delta::ConsolidatedBySameCommit)]);
+    model.push(RepoBranchDeltas {
+        deltas: vec![],
+        error: Some(RepoError::UnbornHead),
+        ..model[0].clone()
+    });
+    let mut out = Vec::<u8>::new();
+
+    assert!(!run(&model, &[Delta::NotConsolidated], &mut out).unwrap());
+    assert!(String::from_utf8(out).unwrap().contains(
+        "release: 1 ConsolidatedBySameCommit\n  a: HEAD points to a branch without any commit\n"
+    ));
+}
+
+#[test]
+fn test_check_failed_without_repos() {
+    let mut out = Vec::<u8>::new();
+
+    assert!(!run(&[], &DEFAULT_FAIL_ON, &mut out).unwrap());
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "Check failed: no repos have been scanned\n"
+    );
+}
@@ -13,9 +13,24 @@ const DEFAULT_CONFIG: &str = r#"
 # You can map keys to custom commands. These commands are
 # executed with disconnected stdin/stdout pipes (to avoid
 # interference with oper-delta's UI). If you want to execute
-# a shell command, wrap the command into a new terminal process.
-# The args field allows substitution of {} with the ID of the
-# currently selected commit.
+# a shell command, wrap the command into a new terminal process
+# or set `foreground = true`: the UI is suspended then and the
+# command runs in the terminal of oper-delta until it exits.
+# With `capture_output = true` oper-delta waits for the command
+# and shows its output and exit status in a popup.
+# With `batch = true` the command is executed (after asking for
+# confirmation) in parallel on all repos currently listed.
+# The args field is split into words like a shell does (use '...'
+# or "..." to keep spaces) and allows substitution of
+#   {}           ID of the selected commit (HEAD if none is selected)
+#   {repo}       path of the repo relative to the .repo folder
+#   {abs_path}   absolute path of the repo
+#   {branch}     selected target branch (fails for pinned revisions)
+#   {head}       ID of HEAD
+#   {target_sha} ID of the tip of the selected target branch
+#   {merge_base} ID of the merge-base of HEAD and the target branch
+# The target branch is the one the selected line in the details
+# belongs to (use j/k to select a line).
 
 # Execute tig in a seperate terminal window
 [[custom_command]]
@@ -23,6 +38,28 @@ key = "t"
 executable = "gnome-terminal"
 args = "-- tig --all"
 
+# Execute tig in the terminal of oper-delta (e.g. via ssh)
+[[custom_command]]
+key = "T"
+executable = "tig"
+args = "--all"
+foreground = true
+
+# Show the commits of HEAD (or the base) which are missing on the selected target branch
+[[custom_command]]
+key = "L"
+executable = "git"
+args = "log --oneline {target_sha}..{head}"
+capture_output = true
+
+# Push HEAD for review on all listed repos (remove the leading
+# '#' to enable it, it pushes without asking per repo)
+#[[custom_command]]
+#key = "P"
+#executable = "git"
+#args = "push origin HEAD:refs/for/{branch}"
+#batch = true
+
 # Open a terminal window in the folder of the selected folder
 [[custom_command]]
 key = "c"
@@ -41,6 +78,15 @@ pub struct CustomCommand {
     pub key: char,
     pub executable: String,
     pub args: Option<String>,
+    /// runs the command attached to the terminal while the UI is suspended
+    #[serde(default)]
+    pub foreground: bool,
+    /// waits for the command and shows its output in a popup
+    #[serde(default)]
+    pub capture_output: bool,
+    /// executes the command on all listed repos
+    #[serde(default)]
+    pub batch: bool,
 }
 
 impl Config {
@@ -59,6 +105,9 @@ impl CustomCommand {
             key,
             executable,
             args,
+            foreground: false,
+            capture_output: false,
+            batch: false,
         }
     }
 }
@@ -129,6 +178,18 @@ fn test_parse_default_config() {
             "gnome-terminal".to_string(),
             Some("-- tig --all".to_string()),
         ),
+        CustomCommand {
+            foreground: true,
+            ..CustomCommand::new('T', "tig".to_string(), Some("--all".to_string()))
+        },
+        CustomCommand {
+            capture_output: true,
+            ..CustomCommand::new(
+                'L',
+                "git".to_string(),
+                Some("log --oneline {target_sha}..{head}".to_string()),
+            )
+        },
         CustomCommand::new(
             'c',
             "gnome-terminal".to_string(),
@@ -0,0 +1,150 @@
+use crate::model::{BranchDelta, RepoBranchDeltas};
+use git2::{Delta, Diff, DiffFormat, DiffOptions, Oid, Patch, Repository};
+
+/// a file which differs between HEAD and a target branch
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChangedFile {
+    pub path: String,
+    /// same letters as used by `git diff --name-status`
+    pub status: char,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// a single line of a unified diff
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffLine {
+    /// '+', '-' or ' ' for content, 'F' for the file header and 'H' for a hunk header
+    pub origin: char,
+    pub content: String,
+}
+
+/// lists all files changed between HEAD and the given target branch
+pub fn changed_files(
+    repo_deltas: &RepoBranchDeltas,
+    branch_delta: &BranchDelta,
+) -> Result<Vec<ChangedFile>, String> {
+    let git_repo = Repository::open(&repo_deltas.repo.abs_path).map_err(|e| e.to_string())?;
+    let diff = diff(
+        &git_repo,
+        repo_deltas,
+        branch_delta,
+        &mut DiffOptions::new(),
+    )?;
+
+    let mut changed_files = vec![];
+    for (n, delta) in diff.deltas().enumerate() {
+        let (_, insertions, deletions) = Patch::from_diff(&diff, n)
+            .map_err(|e| e.to_string())?
+            .map_or(Ok((0, 0, 0)), |patch| patch.line_stats())
+            .map_err(|e| e.to_string())?;
+        let file = match delta.status() {
+            Delta::Deleted => delta.old_file(),
+            _ => delta.new_file(),
+        };
+        changed_files.push(ChangedFile {
+            path: file
+                .path()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            status: status_to_char(delta.status()),
+            insertions,
+            deletions,
+        });
+    }
+    Ok(changed_files)
+}
+
+/// the unified diff of a single file between HEAD and the given target branch
+pub fn file_diff(
+    repo_deltas: &RepoBranchDeltas,
+    branch_delta: &BranchDelta,
+    path: &str,
+) -> Result<Vec<DiffLine>, String> {
+    let git_repo = Repository::open(&repo_deltas.repo.abs_path).map_err(|e| e.to_string())?;
+    let mut options = DiffOptions::new();
+    options.pathspec(path).disable_pathspec_match(true);
+    let diff = diff(&git_repo, repo_deltas, branch_delta, &mut options)?;
+
+    let mut lines = vec![];
+    diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+        let content = String::from_utf8_lossy(line.content());
+        for content in content.trim_end_matches('\n').split('\n') {
+            lines.push(DiffLine {
+                origin: line.origin(),
+                content: content.to_string(),
+            });
+        }
+        true
+    })
+    .map_err(|e| e.to_string())?;
+    Ok(lines)
+}
+
+fn diff<'r>(
+    git_repo: &'r Repository,
+    repo_deltas: &RepoBranchDeltas,
+    branch_delta: &BranchDelta,
+    options: &mut DiffOptions,
+) -> Result<Diff<'r>, String> {
+    let (head_id, target_id) = match (repo_deltas.head_id, branch_delta.target_id) {
+        (Some(head_id), Some(target_id)) => (head_id, target_id),
+        _ => return Err(String::from("branch not found")),
+    };
+    let tree = |id: Oid| git_repo.find_commit(id).and_then(|c| c.tree());
+    let head_tree = tree(head_id).map_err(|e| e.to_string())?;
+    let target_tree = tree(target_id).map_err(|e| e.to_string())?;
+    git_repo
+        .diff_tree_to_tree(Some(&head_tree), Some(&target_tree), Some(options))
+        .map_err(|e| e.to_string())
+}
+
+fn status_to_char(status: Delta) -> char {
+    match status {
+        Delta::Added => 'A',
+        Delta::Deleted => 'D',
+        Delta::Modified => 'M',
+        Delta::Renamed => 'R',
+        Delta::Copied => 'C',
+        Delta::Typechange => 'T',
+        _ => '?',
+    }
+}
+
+#[test]
+fn test_diff() {
+    use crate::model::{calc_branch_deltas_for_a_single_repo, create_synthetic_repo, Repo};
+    use std::sync::Arc;
+
+    let path = std::env::temp_dir().join("oper-delta-diff");
+    let _ = std::fs::remove_dir_all(&path);
+    create_synthetic_repo(&path, 1, 1, 1).unwrap();
+    let repo = Arc::new(Repo::from(path.clone(), String::from("diff")));
+    let repo_deltas = calc_branch_deltas_for_a_single_repo(&repo, "HEAD", &["release"]);
+    let branch_delta = &repo_deltas.deltas[0];
+
+    assert_eq!(
+        changed_files(&repo_deltas, branch_delta),
+        Ok(vec![ChangedFile {
+            path: String::from("file"),
+            status: 'M',
+            insertions: 1,
+            deletions: 1,
+        }])
+    );
+
+    let lines = file_diff(&repo_deltas, branch_delta, "file").unwrap();
+    assert_eq!(lines[0].origin, 'F');
+    assert_eq!(lines[0].content, "diff --git a/file b/file");
+    let changes = lines
+        .iter()
+        .filter(|l| l.origin == '+' || l.origin == '-')
+        .map(|l| (l.origin, l.content.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(changes, [('-', "head 0"), ('+', "target 0")]);
+    assert!(file_diff(&repo_deltas, branch_delta, "other")
+        .unwrap()
+        .is_empty());
+
+    let _ = std::fs::remove_dir_all(&path);
+}
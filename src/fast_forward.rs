@@ -0,0 +1,261 @@
+use crate::model::{rescan, BranchDelta, Delta, RepoBranchDeltas};
+use anyhow::{anyhow, Result};
+use git2::{Branch, BranchType, Repository};
+use std::io::Write;
+
+/// moves the local target branch to the base (HEAD by default); fails (without touching anything)
+/// if the branch can't be moved (see `local_target_branch`) or HEAD is no descendant of it.
+/// With `dry_run` only these preconditions are checked.
+pub fn fast_forward(
+    repo_deltas: &RepoBranchDeltas,
+    branch_delta: &BranchDelta,
+    dry_run: bool,
+) -> Result<(), String> {
+    let (head_id, target_id) = match (repo_deltas.head_id, branch_delta.target_id) {
+        (Some(head_id), Some(target_id))
+            if branch_delta.delta == Delta::NotConsolidatedButFastForwardable =>
+        {
+            (head_id, target_id)
+        }
+        _ => return Err(String::from("not fast forwardable")),
+    };
+
+    let git_repo = Repository::open(&repo_deltas.repo.abs_path).map_err(|e| e.to_string())?;
+    let branch = local_target_branch(&git_repo, branch_delta)?;
+    if !git_repo
+        .graph_descendant_of(head_id, target_id)
+        .unwrap_or(false)
+    {
+        return Err(format!(
+            "{} is no descendant of the branch",
+            repo_deltas.base
+        ));
+    }
+
+    if !dry_run {
+        branch
+            .into_reference()
+            .set_target(
+                head_id,
+                &format!("oper-delta: fast-forward to {}", repo_deltas.base),
+            )
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// returns the local branch of the given target branch if it may be moved, i.e. fails if it
+/// is a remote branch, is checked out (its working tree would be left behind) or has been
+/// moved since the repo has been scanned
+pub fn local_target_branch<'r>(
+    git_repo: &'r Repository,
+    branch_delta: &BranchDelta,
+) -> Result<Branch<'r>, String> {
+    let branch = git_repo
+        .find_branch(&branch_delta.branch_name, BranchType::Local)
+        .map_err(|_| String::from("not a local branch"))?;
+    if branch.is_head() {
+        return Err(String::from("branch is checked out"));
+    }
+    if branch.get().target() != branch_delta.target_id {
+        return Err(String::from("branch has been moved since the scan"));
+    }
+    Ok(branch)
+}
+
+/// e.g. "release 1a2b3c4..5d6e7f8 (2 commits)"
+pub fn describe(repo_deltas: &RepoBranchDeltas, branch_delta: &BranchDelta) -> String {
+    let short = |id: Option<git2::Oid>| match id {
+        Some(id) => id.to_string()[..7].to_string(),
+        None => String::from("?"),
+    };
+    let commits = match &branch_delta.distance_head_to_merge_base {
+        Ok(1) => String::from("1 commit"),
+        Ok(n) => format!("{} commits", n),
+        Err(_) => String::from("? commits"),
+    };
+    format!(
+        "{} {}..{} ({})",
+        branch_delta.branch_name,
+        short(branch_delta.target_id),
+        short(repo_deltas.head_id),
+        commits
+    )
+}
+
+/// fast-forwards all target branches in state `NotConsolidatedButFastForwardable`
+/// to HEAD (or just lists them with `dry_run`); repos which have been changed
+/// are scanned again and their new deltas are listed. Fails if any branch
+/// couldn't be fast-forwarded.
+pub fn run(
+    model: &mut [RepoBranchDeltas],
+    branches: &[&str],
+    dry_run: bool,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let mut nr_of_branches = 0;
+    let mut nr_of_failed_branches = 0;
+    for repo_deltas in model.iter_mut() {
+        let mut changed = false;
+        for branch_delta in repo_deltas
+            .deltas
+            .iter()
+            .filter(|b| b.delta == Delta::NotConsolidatedButFastForwardable)
+        {
+            let description = describe(repo_deltas, branch_delta);
+            match fast_forward(repo_deltas, branch_delta, dry_run) {
+                Ok(()) if dry_run => {
+                    writeln!(
+                        out,
+                        "{}: would fast-forward {}",
+                        repo_deltas.repo.rel_path, description
+                    )?;
+                }
+                Ok(()) => {
+                    changed = true;
+                    writeln!(
+                        out,
+                        "{}: fast-forwarded {}",
+                        repo_deltas.repo.rel_path, description
+                    )?;
+                }
+                Err(e) => {
+                    nr_of_failed_branches += 1;
+                    writeln!(
+                        out,
+                        "{}: failed to fast-forward {}: {}",
+                        repo_deltas.repo.rel_path, description, e
+                    )?;
+                    continue;
+                }
+            }
+            nr_of_branches += 1;
+        }
+
+        if changed {
+            *repo_deltas = rescan(repo_deltas, branches);
+            let deltas = repo_deltas
+                .deltas
+                .iter()
+                .map(|b| format!("{} {}", b.branch_name, b.delta))
+                .collect::<Vec<_>>();
+            writeln!(out, "  now: {}", deltas.join(", "))?;
+        }
+    }
+
+    writeln!(
+        out,
+        "{} {} branches",
+        if dry_run {
+            "Would fast-forward"
+        } else {
+            "Fast-forwarded"
+        },
+        nr_of_branches
+    )?;
+    if nr_of_failed_branches > 0 {
+        return Err(anyhow!(
+            "failed to fast-forward {} branches",
+            nr_of_failed_branches
+        ));
+    }
+    Ok(())
+}
+
+/// creates a repo where HEAD is one commit ahead of "release" and scans it
+/// against the given base
+#[cfg(test)]
+fn test_model(name: &str, base: &str) -> (Repository, git2::Oid, git2::Oid, Vec<RepoBranchDeltas>) {
+    use crate::model::{calc_branch_deltas_for_a_single_repo, create_synthetic_repo, Repo};
+    use std::sync::Arc;
+
+    let path = std::env::temp_dir().join(format!("oper-delta-fast-forward-{}", name));
+    let _ = std::fs::remove_dir_all(&path);
+    let git_repo = create_synthetic_repo(&path, 1, 1, 0).unwrap();
+    let release = git_repo.refname_to_id("refs/heads/release").unwrap();
+    let head = git_repo.refname_to_id("refs/heads/main").unwrap();
+
+    let repo = Arc::new(Repo::from(path, name.to_string()));
+    let model = vec![calc_branch_deltas_for_a_single_repo(
+        &repo,
+        base,
+        &["release"],
+    )];
+    (git_repo, release, head, model)
+}
+
+#[test]
+fn test_fast_forward() {
+    let (git_repo, release, head, mut model) = test_model("ff", "HEAD");
+    assert_eq!(
+        model[0].deltas[0].delta,
+        Delta::NotConsolidatedButFastForwardable
+    );
+
+    let mut out = Vec::<u8>::new();
+    run(&mut model, &["release"], true, &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        format!(
+            "ff: would fast-forward release {}..{} (1 commit)\nWould fast-forward 1 branches\n",
+            &release.to_string()[..7],
+            &head.to_string()[..7]
+        )
+    );
+    assert_eq!(
+        git_repo.refname_to_id("refs/heads/release").unwrap(),
+        release
+    );
+
+    let mut out = Vec::<u8>::new();
+    run(&mut model, &["release"], false, &mut out).unwrap();
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .ends_with("  now: release ConsolidatedBySameCommit\nFast-forwarded 1 branches\n"));
+    assert_eq!(git_repo.refname_to_id("refs/heads/release").unwrap(), head);
+    assert_eq!(model[0].deltas[0].delta, Delta::ConsolidatedBySameCommit);
+
+    let _ = std::fs::remove_dir_all(git_repo.workdir().unwrap());
+}
+
+#[test]
+fn test_fast_forward_moved_branch() {
+    use crate::model::synthetic_commit;
+
+    let (git_repo, release, _, mut model) = test_model("moved", "HEAD");
+    let moved = synthetic_commit(&git_repo, Some(release), &[("file", "moved")]).unwrap();
+    git_repo
+        .reference("refs/heads/release", moved, true, "")
+        .unwrap();
+
+    let mut out = Vec::<u8>::new();
+    assert!(run(&mut model, &["release"], false, &mut out).is_err());
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .contains("failed to fast-forward release"));
+    assert_eq!(git_repo.refname_to_id("refs/heads/release").unwrap(), moved);
+
+    let _ = std::fs::remove_dir_all(git_repo.workdir().unwrap());
+}
+
+#[test]
+fn test_fast_forward_checked_out_branch() {
+    let (git_repo, release, _, mut model) = test_model("checked-out", "main");
+    git_repo.set_head("refs/heads/release").unwrap();
+    assert_eq!(
+        model[0].deltas[0].delta,
+        Delta::NotConsolidatedButFastForwardable
+    );
+
+    let mut out = Vec::<u8>::new();
+    assert!(run(&mut model, &["release"], false, &mut out).is_err());
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .contains("branch is checked out"));
+    assert_eq!(
+        git_repo.refname_to_id("refs/heads/release").unwrap(),
+        release
+    );
+
+    let _ = std::fs::remove_dir_all(git_repo.workdir().unwrap());
+}
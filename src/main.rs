@@ -10,8 +10,14 @@ extern crate serde;
 extern crate spsheet;
 extern crate toml;
 
+mod batch;
+mod check;
 mod config;
+mod diff;
+mod fast_forward;
 mod manifest;
+mod manifest_delta;
+mod merge;
 mod model;
 mod report;
 mod styles;
@@ -21,16 +27,19 @@ mod views;
 
 use anyhow::Result;
 use clap::{App, Arg};
-use model::{create_model, Filter};
+use model::{create_model, Delta, Filter, PINNED_PREFIX};
 use std::env;
 use std::fs::File;
 use std::path::Path;
-use utils::{find_project_file, repos_from};
+use utils::{find_project_file, find_repo_folder, pin_repos, pinned_target_name, repos_from};
 
+const CHECK_FAILED_EXIT_CODE: i32 = 2; //1 is used by rust for errors returned by main
 const MAX_NUMBER_OF_THREADS: usize = 18; //tests on a 36 core INTEL Xeon showed that parsing becomes slower again if more than 18 threads are used
 
 fn main() -> Result<(), String> {
     let original_cwd = env::current_dir().expect("cwd not found");
+    let delta_names = Delta::ALL.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+    let delta_names = delta_names.iter().map(|s| s.as_str()).collect::<Vec<_>>();
     let matches = App::new("oper-delta")
         .version(crate_version!())
         .author("Florian Bramer <elektronenhirn@gmail.com>")
@@ -55,16 +64,87 @@ fn main() -> Result<(), String> {
             Arg::with_name("report")
             .long("report")
             .value_name("file")
-            .help("writes a report to a file given by <path> - supported formats: .csv, .ods, .xlsx")
+            .help("writes a report to a file given by <path> - supported formats: .csv, .ods, .xlsx, .json, .ndjson, .md, .html")
             .takes_value(true)
         )
+        .arg(
+            Arg::with_name("format")
+            .long("format")
+            .value_name("format")
+            .help("writes a machine-readable report to stdout instead of showing the UI")
+            .possible_values(&["json", "ndjson"])
+            .conflicts_with("report")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("check")
+            .long("check")
+            .help("non-interactive mode for CI: prints a summary and exits with code 2 if a repo is in one of the --fail-on states")
+        )
+        .arg(
+            Arg::with_name("fail-on")
+            .long("fail-on")
+            .value_name("delta")
+            .help("states which let --check fail [default: NotConsolidatedButFastForwardable NotConsolidated BranchNotFound]")
+            .possible_values(&delta_names)
+            .requires("check")
+            .takes_value(true)
+            .multiple(true)
+            .use_delimiter(true)
+            .number_of_values(1)
+        )
+        .arg(
+            Arg::with_name("fast-forward")
+            .long("fast-forward")
+            .help("fast-forwards all local target branches which are NotConsolidatedButFastForwardable to HEAD instead of showing the UI")
+        )
+        .arg(
+            Arg::with_name("dry-run")
+            .long("dry-run")
+            .help("only lists the branches which would be fast-forwarded")
+            .requires("fast-forward")
+        )
+        .arg(
+            Arg::with_name("diff-stats")
+            .long("diff-stats")
+            .help("counts the changed files and lines between HEAD, every target branch and their merge-base for the reports (slows down scanning)")
+        )
+        .arg(
+            Arg::with_name("trial-merge")
+            .long("trial-merge")
+            .help("merges HEAD into every target branch in memory to forecast conflicts (slows down scanning)")
+        )
+        .arg(
+            Arg::with_name("base")
+            .long("base")
+            .value_name("ref")
+            .help("branch, tag, remote ref or sha (resolved per repo) to compare the target branches against instead of HEAD")
+            .default_value(model::DEFAULT_BASE)
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("pinned")
+            .long("pinned")
+            .value_name("manifest")
+            .help("compares (in addition to the given branches) against the revision each project is pinned to by the given manifest")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("manifest-delta")
+            .long("manifest-delta")
+            .value_names(&["old", "new"])
+            .help("compares the revisions pinned by the <new> manifest against the ones pinned by the <old> manifest and lists added, removed and moved projects instead of showing the UI")
+            .number_of_values(2)
+            .conflicts_with_all(&["branch", "base", "pinned", "fast-forward"])
+        )
         .arg(
             Arg::with_name("branch")
                 .value_name("branch")
-                .help("one or multiple branches to diff current HEAD against")
+                .help("one or multiple branches to diff the base (current HEAD by default) against")
                 .takes_value(true)
+                .validator(validate_branch_name)
                 .multiple(true)
-                .required(true),
+                .required_unless_one(&["pinned", "manifest-delta"]),
         )
         .arg(
             Arg::with_name("hide-consolidated-by-same-commit")
@@ -81,6 +161,11 @@ fn main() -> Result<(), String> {
                 .long("hide-consolidated-by-equal-content")
                 .help("hide repositories where the HEAD and <branch> have equal content but are not related by history"),
         )
+        .arg(
+            Arg::with_name("hide-consolidated-by-cherry-pick")
+                .long("hide-consolidated-by-cherry-pick")
+                .help("hide repositories where all commits of HEAD have been cherry-picked into given <branch>"),
+        )
         .arg(
             Arg::with_name("hide-non-consolidated")
                 .long("hide-non-consolidated")
@@ -96,6 +181,11 @@ fn main() -> Result<(), String> {
                 .long("hide-branch-not-found")
                 .help("hide repositories where the given <branch> couldn't been found"),
         )
+        .arg(
+            Arg::with_name("hide-repo-errors")
+                .long("hide-repo-errors")
+                .help("hide repositories which couldn't be scanned (e.g. failed to open, HEAD without commits)"),
+        )
         .arg(
             Arg::with_name("manifest")
                 .short("m")
@@ -105,7 +195,11 @@ fn main() -> Result<(), String> {
         )
         .get_matches();
 
-    let branches = matches.values_of("branch").unwrap().collect::<Vec<_>>();
+    let branches = matches
+        .values_of("branch")
+        .map(|values| values.collect::<Vec<_>>())
+        .unwrap_or_default();
+    let base = matches.value_of("base").unwrap();
     let cwd = Path::new(matches.value_of("cwd").unwrap());
     let filter = Filter {
         include_consolidated_by_same_commit: !matches
@@ -114,25 +208,87 @@ fn main() -> Result<(), String> {
             .is_present("hide-consolidated-by-merge-commit"),
         include_consolidated_by_equal_content: !matches
             .is_present("hide-consolidated-by-equal-content"),
+        include_consolidated_by_cherry_pick: !matches
+            .is_present("hide-consolidated-by-cherry-pick"),
         include_non_consolidated: !matches.is_present("hide-non-consolidated"),
         include_non_consolidated_but_ff_able: !matches
             .is_present("hide-non-consolidated-but-ff-able"),
         include_branch_not_found: !matches.is_present("hide-branch-not-found"),
+        include_repo_errors: !matches.is_present("hide-repo-errors"),
         repo_ignore_list: matches.value_of("repo-ignore-list").map(|x| x.to_string()),
     };
     let report_file_path = matches.value_of("report").map(|x| x.to_string());
+    let output_format = matches.value_of("format");
+    let check = if matches.is_present("check") {
+        Some(match matches.values_of("fail-on") {
+            Some(values) => values.map(|v| v.parse::<Delta>()).collect::<Result<_, _>>()?,
+            None => check::DEFAULT_FAIL_ON.to_vec(),
+        })
+    } else {
+        None
+    };
     let filter_by_manifest = matches.value_of("manifest");
+    let pinned_by_manifest = matches.value_of("pinned");
+    let manifest_delta = matches
+        .values_of("manifest-delta")
+        .map(|mut values| (values.next().unwrap(), values.next().unwrap()));
+    let diff_stats = matches.is_present("diff-stats");
+    let trial_merge = matches.is_present("trial-merge");
+    let fast_forward = if matches.is_present("fast-forward") {
+        Some(matches.is_present("dry-run"))
+    } else {
+        None
+    };
 
-    do_main(branches, cwd, filter, report_file_path, filter_by_manifest).map_err(|e| e.to_string())
+    do_main(
+        base,
+        branches,
+        cwd,
+        filter,
+        report_file_path,
+        output_format,
+        check,
+        fast_forward,
+        diff_stats,
+        trial_merge,
+        filter_by_manifest,
+        pinned_by_manifest,
+        manifest_delta,
+    )
+    .map_err(|e| e.to_string())
+    .map(|passed| {
+        if !passed {
+            std::process::exit(CHECK_FAILED_EXIT_CODE);
+        }
+    })
+}
+
+/// rejects branch names which do not follow git's ref-format rules
+/// (see `git check-ref-format --branch`)
+fn validate_branch_name(name: String) -> Result<(), String> {
+    if !name.starts_with('-') && git2::Reference::is_valid_name(&format!("refs/heads/{}", name)) {
+        Ok(())
+    } else {
+        Err(format!("'{}' is not a valid git branch name", name))
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn do_main(
+    base: &str,
     branches: Vec<&str>,
     cwd: &Path,
     filter: Filter,
     report_file_path: Option<String>,
-    filter_by_manifest: Option<&str>
-) -> Result<()> {
+    output_format: Option<&str>,
+    check: Option<Vec<Delta>>,
+    fast_forward: Option<bool>,
+    diff_stats: bool,
+    trial_merge: bool,
+    filter_by_manifest: Option<&str>,
+    pinned_by_manifest: Option<&str>,
+    manifest_delta: Option<(&str, &str)>,
+) -> Result<bool> {
     let config = config::read();
 
     env::set_current_dir(cwd)?;
@@ -141,24 +297,112 @@ fn do_main(
         .build_global()
         .unwrap();
 
+    //all manifests are read like repo does, including the local manifests of the workspace
+    let local_manifests = find_repo_folder()?.join("local_manifests");
+    let parse_manifest =
+        |file: &str| manifest::parse_with_local_manifests(Path::new(file), &local_manifests);
+
     let project_file = File::open(find_project_file()?)?;
     let mut repos = repos_from(&project_file, false)?;
     if let Some(manifest_file) = filter_by_manifest {
-        let manifest = manifest::parse(Path::new(&manifest_file))?;
+        let manifest = parse_manifest(manifest_file)?;
         repos.retain(|repo| manifest.projects.iter().find(|&p| repo.rel_path == p.path ).is_some());
     }
+    let pinned_target;
+    let mut branches = branches;
+    if let Some(manifest_file) = pinned_by_manifest {
+        let manifest = parse_manifest(manifest_file)?;
+        pinned_target = pinned_target_name(Path::new(manifest_file));
+        repos = pin_repos(repos, &pinned_target, &manifest.pinned_revisions());
+        branches.push(&pinned_target);
+    }
+    let (old_target, new_target);
+    let mut base = base;
+    let mut changed_projects = None;
+    if let Some((old_file, new_file)) = manifest_delta {
+        let old = parse_manifest(old_file)?;
+        let new = parse_manifest(new_file)?;
+        let delta = manifest_delta::compare(&old, &new);
+        new_target = pinned_target_name(Path::new(new_file));
+        old_target = match pinned_target_name(Path::new(old_file)) {
+            name if name == new_target => format!("{}{}", PINNED_PREFIX, old_file),
+            name => name,
+        };
+        repos = pin_repos(repos, &new_target, &new.pinned_revisions());
+        repos = pin_repos(repos, &old_target, &delta.old_pinned_revisions(&old));
+        repos.retain(|repo| {
+            delta
+                .kept
+                .iter()
+                .chain(delta.moved.iter())
+                .any(|(_, p)| repo.rel_path == p.path)
+        });
+        base = &old_target;
+        branches = vec![&new_target];
+        changed_projects = Some(delta);
+    }
     let nr_of_total_repos = repos.len();
 
-    let model = create_model(repos, branches, filter)?;
+    let mut model = create_model(repos, base, branches.clone(), &filter, diff_stats, trial_merge)?;
+
+    //the TUI filters on its own, so that the filter can be changed interactively
+    if report_file_path.is_none()
+        && output_format.is_none()
+        && check.is_none()
+        && fast_forward.is_none()
+        && changed_projects.is_none()
+    {
+        ui::show(model, filter, &config, nr_of_total_repos);
+        return Ok(true);
+    }
+    model.retain(|repo| filter.includes(repo));
+
+    //fast-forward first, so that a check or report reflects the new state
+    if let Some(dry_run) = fast_forward {
+        let mut out: Box<dyn std::io::Write> = match output_format {
+            Some(_) => Box::new(std::io::stderr()),
+            None => Box::new(std::io::stdout()),
+        };
+        fast_forward::run(&mut model, &branches, dry_run, &mut out)?;
+    }
+
+    if let Some(delta) = &changed_projects {
+        let mut out: Box<dyn std::io::Write> = match output_format {
+            Some(_) => Box::new(std::io::stderr()),
+            None => Box::new(std::io::stdout()),
+        };
+        manifest_delta::run(delta, &model, &mut out)?;
+    }
+
+    //the summary of a check must not interfere with a report written to stdout
+    let check_passed = match &check {
+        Some(fail_on) if output_format.is_some() => {
+            check::run(&model, fail_on, &mut std::io::stderr())?
+        }
+        Some(fail_on) => check::run(&model, fail_on, &mut std::io::stdout())?,
+        None => true,
+    };
 
-    //TUI or report?
-    match report_file_path {
-        None => ui::show(model, &config, nr_of_total_repos),
-        Some(file) => {
+    match (report_file_path, output_format) {
+        (Some(file), _) => {
             println!("Skipping UI - generating report...");
             report::generate(model, &file)?
         }
+        (None, Some(format)) => report::print(model, format)?,
+        (None, None) => {}
     }
 
-    Ok(())
+    Ok(check_passed)
+}
+
+#[test]
+fn test_validate_branch_name() {
+    assert!(validate_branch_name("release".to_string()).is_ok());
+    assert!(validate_branch_name("origin/release-1.2".to_string()).is_ok());
+    //valid ref names: branch names are safe because they never reach a shell (git is
+    //accessed via libgit2), not because of this validation
+    assert!(validate_branch_name("a;b".to_string()).is_ok());
+    assert!(validate_branch_name("$(x)".to_string()).is_ok());
+    assert!(validate_branch_name("--help".to_string()).is_err());
+    assert!(validate_branch_name("release..x".to_string()).is_err());
 }
@@ -1,43 +1,261 @@
+use crate::model::PinnedRevision;
 use serde_xml_rs::from_reader;
+use serde::de::IgnoredAny;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::BufReader;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use anyhow::{anyhow,Result};
 use serde::Deserialize;
 
+/// parses a manifest like repo does: includes are expanded in place, `<remove-project>` and
+/// `<extend-project>` apply to the projects declared before them
 pub fn parse(path: &Path) -> Result<Manifest>{
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let mut manifest: Manifest = from_reader(reader)?;
-    let includes : Vec<String> = manifest.includes.iter().map(|i| i.name.clone()).collect();
-    for include in &includes {
-        let path = path.with_file_name(include);
-        let child = parse(&path).or_else(|e| Err(anyhow!("Failed to parse {}: {}", include, e)))?;
-        manifest.append(&child);
+    let mut manifest = Manifest::empty();
+    manifest.merge(path)?;
+    Ok(manifest)
+}
+
+/// like `parse()` but also merges the local manifests (all `*.xml` files in the given
+/// folder, usually `.repo/local_manifests`) in alphabetical order, if there are any
+pub fn parse_with_local_manifests(path: &Path, local_manifests: &Path) -> Result<Manifest>{
+    let mut manifest = parse(path)?;
+    if local_manifests.is_dir() {
+        let mut files : Vec<PathBuf> = std::fs::read_dir(local_manifests)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<_, _>>()?;
+        files.retain(|f| f.extension().is_some_and(|e| e == "xml"));
+        files.sort();
+        for file in &files {
+            manifest.merge(file).map_err(|e| anyhow!("Failed to parse {}: {}", file.display(), e))?;
+        }
     }
     Ok(manifest)
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug)]
 pub struct Manifest {
-    #[serde(rename = "project", default)]
     pub projects: Vec<Project>,
-    #[serde(rename = "include", default)]
-    pub includes: Vec<Include>,
+    pub remotes: Vec<Remote>,
+    pub default: Option<Default>,
+}
+
+/// the elements of a manifest file in the order they are declared
+#[derive(Debug, Deserialize)]
+struct ManifestFile {
+    #[serde(rename = "$value", default)]
+    elements: Vec<Element>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Element {
+    Project(Project),
+    Include(Include),
+    RemoveProject(RemoveProject),
+    ExtendProject(ExtendProject),
+    Default(Default),
+    Remote(Remote),
+    //not relevant for the set of projects
+    Notice(IgnoredAny),
+    ManifestServer(IgnoredAny),
+    Submanifest(IgnoredAny),
+    RepoHooks(IgnoredAny),
+    Superproject(IgnoredAny),
+    Contactinfo(IgnoredAny),
+    #[serde(other)]
+    Unknown,
 }
 
 impl Manifest {
-    pub fn append(&mut self, manifest: &Manifest){
-        let projects = &manifest.projects;
-        self.projects.extend(projects.iter().cloned());
+    fn empty() -> Manifest {
+        Manifest { projects: vec![], remotes: vec![], default: None }
+    }
+
+    /// applies all elements of the given manifest file (and its includes)
+    fn merge(&mut self, path: &Path) -> Result<()> {
+        let file = File::open(path)?;
+        let manifest_file: ManifestFile = from_reader(BufReader::new(file))?;
+        for element in manifest_file.elements {
+            match element {
+                Element::Project(project) => self.add_project(project, None)?,
+                Element::Include(include) => {
+                    let path = path.with_file_name(&include.name);
+                    self.merge(&path).map_err(|e| anyhow!("Failed to parse {}: {}", include.name, e))?;
+                }
+                Element::RemoveProject(remove) => self.remove_project(&remove)?,
+                Element::ExtendProject(extend) => self.extend_project(&extend)?,
+                Element::Default(default) => self.set_default(default)?,
+                Element::Remote(remote) => self.add_remote(remote)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// adds the project and its nested projects; like repo does, the path defaults to the name
+    /// and the name and path of a nested project are relative to the ones of its parent
+    fn add_project(&mut self, mut project: Project, parent: Option<&Project>) -> Result<()> {
+        if project.path.is_empty() {
+            project.path = project.name.clone();
+        }
+        if let Some(parent) = parent {
+            project.name = format!("{}/{}", parent.name, project.name);
+            project.path = format!("{}/{}", parent.path, project.path);
+        }
+        if self.projects.iter().any(|p| p.path == project.path) {
+            return Err(anyhow!("duplicate path {} in manifest", project.path));
+        }
+        let children = std::mem::take(&mut project.children);
+        self.projects.push(project.clone());
+        for child in children {
+            if let ProjectChild::Project(child) = child {
+                self.add_project(child, Some(&project))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// removes all projects of the given name (and path, if given)
+    fn remove_project(&mut self, remove: &RemoveProject) -> Result<()> {
+        if remove.name.is_none() && remove.path.is_none() {
+            return Err(anyhow!("remove-project element specifies neither name nor path"));
+        }
+        let nr_of_projects = self.projects.len();
+        self.projects.retain(|p| !remove.matches(p));
+        if self.projects.len() == nr_of_projects && !remove.optional() {
+            return Err(anyhow!("remove-project element specifies non-existent project: {}", remove));
+        }
+        Ok(())
+    }
+
+    /// changes all projects of the given name (and path, if given)
+    fn extend_project(&mut self, extend: &ExtendProject) -> Result<()> {
+        let mut projects : Vec<&mut Project> = self.projects.iter_mut()
+            .filter(|p| p.name == extend.name && extend.path.as_ref().is_none_or(|path| *path == p.path))
+            .collect();
+        if projects.is_empty() {
+            if extend.optional() {
+                return Ok(());
+            }
+            return Err(anyhow!("extend-project element specifies non-existent project: {}", extend.name));
+        }
+        if extend.dest_path.is_some() && extend.path.is_none() && projects.len() > 1 {
+            return Err(anyhow!("extend-project cannot use dest-path when matching multiple projects: {}", extend.name));
+        }
+        for project in projects.iter_mut() {
+            if let Some(groups) = &extend.groups {
+                project.groups = if project.groups.is_empty() { groups.clone() } else { format!("{},{}", project.groups, groups) };
+            }
+            if let Some(revision) = &extend.revision {
+                project.revision = Some(revision.clone());
+            }
+            if let Some(remote) = &extend.remote {
+                project.remote = Some(remote.clone());
+            }
+            if let Some(dest_path) = &extend.dest_path {
+                project.path = dest_path.clone();
+            }
+        }
+        Ok(())
+    }
+
+    fn set_default(&mut self, default: Default) -> Result<()> {
+        match &self.default {
+            Some(existing) if *existing != default => Err(anyhow!("duplicate default in manifest")),
+            _ => {
+                self.default = Some(default);
+                Ok(())
+            }
+        }
+    }
+
+    fn add_remote(&mut self, remote: Remote) -> Result<()> {
+        match self.remote(&remote.name) {
+            Some(existing) if *existing != remote => Err(anyhow!("remote {} already exists with different attributes", remote.name)),
+            Some(_) => Ok(()),
+            None => {
+                self.remotes.push(remote);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn remote(&self, name: &str) -> Option<&Remote> {
+        self.remotes.iter().find(|r| r.name == name)
+    }
+
+    /// the revision the given project is pinned to, falls back to the revision
+    /// of its `<remote>` and then to the `<default revision>`
+    pub fn revision_of<'a>(&'a self, project: &'a Project) -> Option<&'a str> {
+        project.revision.as_deref()
+            .or_else(|| self.remote(self.remote_of(project)?)?.revision.as_deref())
+            .or_else(|| self.default.as_ref()?.revision.as_deref())
+    }
+
+    /// the remote of the given project, falls back to the `<default remote>`
+    pub fn remote_of<'a>(&'a self, project: &'a Project) -> Option<&'a str> {
+        project.remote.as_deref().or_else(|| self.default.as_ref()?.remote.as_deref())
+    }
+
+    /// the pinned revisions of all projects by their path
+    pub fn pinned_revisions(&self) -> HashMap<String, PinnedRevision> {
+        self.projects.iter().filter_map(|p| Some((p.path.clone(), self.pinned_revision(p)?))).collect()
+    }
+
+    /// None if neither the project nor the manifest define a revision; the remote
+    /// is named by its alias (if any) as it is in the checkout
+    pub fn pinned_revision(&self, project: &Project) -> Option<PinnedRevision> {
+        let remote = self.remote_of(project).map(|name| match self.remote(name) {
+            Some(Remote { alias: Some(alias), .. }) => alias.clone(),
+            _ => name.to_string(),
+        });
+        Some(PinnedRevision {
+            revision: self.revision_of(project)?.to_string(),
+            remote,
+        })
     }
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Project {
     pub name: String,
+    /// the name if not given in the manifest
+    #[serde(default)]
     pub path: String,
+    #[serde(default)]
     pub groups: String,
+    pub revision: Option<String>,
+    pub remote: Option<String>,
+    /// empty once the project has been added to a `Manifest`
+    #[serde(rename = "$value", default)]
+    children: Vec<ProjectChild>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+enum ProjectChild {
+    Project(Project),
+    //not relevant for the set of projects
+    Copyfile(IgnoredAny),
+    Linkfile(IgnoredAny),
+    Annotation(IgnoredAny),
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Default {
+    pub revision: Option<String>,
+    pub remote: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Remote {
+    pub name: String,
+    pub alias: Option<String>,
+    pub fetch: Option<String>,
+    pub revision: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -45,6 +263,52 @@ pub struct Include {
     pub name: String,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+struct RemoveProject {
+    name: Option<String>,
+    path: Option<String>,
+    optional: Option<String>,
+}
+
+impl RemoveProject {
+    fn matches(&self, project: &Project) -> bool {
+        self.name.as_ref().is_none_or(|name| *name == project.name) && self.path.as_ref().is_none_or(|path| *path == project.path)
+    }
+
+    fn optional(&self) -> bool {
+        self.optional.as_deref() == Some("true")
+    }
+}
+
+impl std::fmt::Display for RemoveProject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name.as_ref().or(self.path.as_ref()).map_or("", |s| s.as_str()))
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+struct ExtendProject {
+    name: String,
+    path: Option<String>,
+    dest_path: Option<String>,
+    groups: Option<String>,
+    revision: Option<String>,
+    remote: Option<String>,
+    optional: Option<String>,
+}
+
+impl ExtendProject {
+    fn optional(&self) -> bool {
+        self.optional.as_deref() == Some("true")
+    }
+}
+
+#[cfg(test)]
+fn paths(manifest: &Manifest) -> Vec<&str> {
+    manifest.projects.iter().map(|p| p.path.as_str()).collect()
+}
+
 #[test]
 fn test_parse() {
     let manifest = parse(Path::new("test/upstream.xml")).unwrap();
@@ -55,4 +319,62 @@ fn test_parse() {
 fn test_parse_recursive() {
     let manifest = parse(Path::new("test/default.xml")).unwrap();
     assert_eq!(manifest.projects.len(), 3);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_revision() {
+    let manifest = parse(Path::new("test/pinned.xml")).unwrap();
+    let revisions : Vec<Option<&str>> = manifest.projects.iter().map(|p| manifest.revision_of(p)).collect();
+    assert_eq!(revisions, vec![Some("7295712444f9e66fd0f52a298c1c39ccb0194ad4"), Some("refs/tags/v1.0"), Some("main")]);
+    let remotes : Vec<Option<&str>> = manifest.projects.iter().map(|p| manifest.remote_of(p)).collect();
+    assert_eq!(remotes, vec![Some("origin"), Some("origin"), Some("upstream")]);
+}
+
+#[test]
+fn test_remove_project() {
+    let manifest = parse(Path::new("test/remove-project.xml")).unwrap();
+    assert_eq!(paths(&manifest), vec!["frameworks/base", "build/soong"]);
+    assert!(parse(Path::new("test/remove-project-non-existent.xml")).is_err());
+}
+
+#[test]
+fn test_extend_project() {
+    let manifest = parse(Path::new("test/extend-project.xml")).unwrap();
+    assert_eq!(paths(&manifest), vec!["build/blueprint", "prebuilts/kati"]);
+    let kati = &manifest.projects[1];
+    assert_eq!(kati.groups, "upstream-project,pdk");
+    assert_eq!(kati.revision.as_deref(), Some("refs/heads/kati-next"));
+    assert_eq!(kati.remote.as_deref(), Some("github"));
+    assert!(parse(Path::new("test/extend-project-non-existent.xml")).is_err());
+}
+
+#[test]
+fn test_default_and_remote() {
+    let manifest = parse(Path::new("test/remote.xml")).unwrap();
+    let pinned : Vec<PinnedRevision> = manifest.projects.iter().map(|p| manifest.pinned_revision(p).unwrap()).collect();
+    let pinned : Vec<(&str, Option<&str>)> = pinned.iter().map(|p| (p.revision.as_str(), p.remote.as_deref())).collect();
+    assert_eq!(pinned, vec![("main", Some("aosp")), ("android-11.0.0_r1", Some("aosp")), ("master", Some("github"))]);
+    assert!(parse(Path::new("test/duplicate-default.xml")).is_err());
+}
+
+#[test]
+fn test_optional_attributes_and_nested_projects() {
+    let manifest = parse(Path::new("test/nested.xml")).unwrap();
+    let names : Vec<&str> = manifest.projects.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["platform/build", "platform/build/soong", "platform/build/soong/bp", "device/acme", "device/acme/common", "platform/art"]);
+    assert_eq!(paths(&manifest), vec!["build/make", "build/make/soong", "build/make/soong/blueprint", "device/acme", "device/acme/common", "art"]);
+    assert_eq!(manifest.projects[1].revision.as_deref(), Some("refs/tags/v2"));
+    assert_eq!(manifest.revision_of(&manifest.projects[2]), Some("main"));
+    assert_eq!(manifest.projects[5].groups, "pdk");
+    assert!(manifest.projects[0].groups.is_empty());
+}
+
+#[test]
+fn test_local_manifests() {
+    let manifest = parse_with_local_manifests(Path::new("test/upstream.xml"), Path::new("test/local_manifests")).unwrap();
+    assert_eq!(paths(&manifest), vec!["build/kati", "vendor/extras", "device/acme"]);
+    assert_eq!(manifest.projects[0].revision.as_deref(), Some("refs/heads/local"));
+
+    let manifest = parse_with_local_manifests(Path::new("test/upstream.xml"), Path::new("test/no_local_manifests")).unwrap();
+    assert_eq!(manifest.projects.len(), 2);
+}
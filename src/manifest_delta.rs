@@ -0,0 +1,148 @@
+use crate::manifest::{Manifest, Project};
+use crate::model::{PinnedRevision, RepoBranchDeltas};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// the projects which differ between two manifests
+#[derive(Debug, Default)]
+pub struct ManifestDelta {
+    /// projects only in the new manifest
+    pub added: Vec<Project>,
+    /// projects only in the old manifest
+    pub removed: Vec<Project>,
+    /// projects with the same name but another path (old, new)
+    pub moved: Vec<(Project, Project)>,
+    /// projects with the same name and path (old, new)
+    pub kept: Vec<(Project, Project)>,
+}
+
+/// matches the projects of both manifests by name and path first, the
+/// remaining ones by name only (these have been moved)
+pub fn compare(old: &Manifest, new: &Manifest) -> ManifestDelta {
+    let mut delta = ManifestDelta::default();
+    let mut old_projects = old.projects.iter().collect::<Vec<_>>();
+    let mut unmatched = vec![];
+
+    for new_project in &new.projects {
+        match old_projects
+            .iter()
+            .position(|p| p.name == new_project.name && p.path == new_project.path)
+        {
+            Some(n) => delta
+                .kept
+                .push((old_projects.remove(n).clone(), new_project.clone())),
+            None => unmatched.push(new_project),
+        }
+    }
+    for new_project in unmatched {
+        match old_projects.iter().position(|p| p.name == new_project.name) {
+            Some(n) => delta
+                .moved
+                .push((old_projects.remove(n).clone(), new_project.clone())),
+            None => delta.added.push(new_project.clone()),
+        }
+    }
+    delta.removed = old_projects.into_iter().cloned().collect();
+    delta
+}
+
+impl ManifestDelta {
+    /// the revisions the old manifest pins the projects to, by the path of the
+    /// new manifest (where the projects are checked out after a move)
+    pub fn old_pinned_revisions(&self, old: &Manifest) -> HashMap<String, PinnedRevision> {
+        self.kept
+            .iter()
+            .chain(self.moved.iter())
+            .filter_map(|(old_project, new_project)| {
+                Some((new_project.path.clone(), old.pinned_revision(old_project)?))
+            })
+            .collect()
+    }
+}
+
+/// lists the added, removed and moved projects and the deltas of all projects
+/// which are part of both manifests, grouped by delta
+pub fn run(
+    manifest_delta: &ManifestDelta,
+    model: &[RepoBranchDeltas],
+    out: &mut dyn Write,
+) -> Result<()> {
+    for project in &manifest_delta.added {
+        writeln!(out, "added: {} ({})", project.path, project.name)?;
+    }
+    for project in &manifest_delta.removed {
+        writeln!(out, "removed: {} ({})", project.path, project.name)?;
+    }
+    for (old_project, new_project) in &manifest_delta.moved {
+        writeln!(
+            out,
+            "moved: {} -> {} ({})",
+            old_project.path, new_project.path, new_project.name
+        )?;
+    }
+
+    let mut deltas = Vec::<(String, Vec<&str>)>::new();
+    for repo in model {
+        let delta = match (&repo.error, repo.deltas.first()) {
+            (Some(e), _) => e.to_string(),
+            (None, Some(branch_delta)) => branch_delta.delta.to_string(),
+            (None, None) => continue,
+        };
+        match deltas.iter_mut().find(|(d, _)| *d == delta) {
+            Some((_, paths)) => paths.push(&repo.repo.rel_path),
+            None => deltas.push((delta, vec![&repo.repo.rel_path])),
+        }
+    }
+    for (delta, paths) in &deltas {
+        writeln!(out, "{} ({}):", delta, paths.len())?;
+        for path in paths {
+            writeln!(out, "  {}", path)?;
+        }
+    }
+
+    writeln!(
+        out,
+        "{} projects compared, {} added, {} removed, {} moved",
+        model.len(),
+        manifest_delta.added.len(),
+        manifest_delta.removed.len(),
+        manifest_delta.moved.len()
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_compare() {
+    use crate::manifest::parse;
+    use std::path::Path;
+
+    let old = parse(Path::new("test/upstream.xml")).unwrap();
+    let new = parse(Path::new("test/pinned.xml")).unwrap();
+    let manifest_delta = compare(&old, &new);
+    let paths = |projects: &[Project]| projects.iter().map(|p| p.path.clone()).collect::<Vec<_>>();
+    assert_eq!(
+        paths(&manifest_delta.added),
+        ["build/soong", "frameworks/base"]
+    );
+    assert_eq!(paths(&manifest_delta.removed), ["build/blueprint"]);
+    assert!(manifest_delta.moved.is_empty());
+    assert_eq!(manifest_delta.kept.len(), 1);
+
+    let old_pins = manifest_delta.old_pinned_revisions(&old);
+    assert_eq!(
+        old_pins["build/kati"].revision,
+        "7295712444f9e66fd0f52a298c1c39ccb0194ad4"
+    );
+    assert_eq!(old_pins.len(), 1);
+
+    let new = parse(Path::new("test/moved.xml")).unwrap();
+    let manifest_delta = compare(&old, &new);
+    assert!(manifest_delta.added.is_empty() && manifest_delta.removed.is_empty());
+    assert_eq!(manifest_delta.moved.len(), 1);
+    assert_eq!(manifest_delta.moved[0].0.path, "build/kati");
+    assert_eq!(manifest_delta.moved[0].1.path, "prebuilts/kati");
+    assert!(manifest_delta
+        .old_pinned_revisions(&old)
+        .contains_key("prebuilts/kati"));
+}
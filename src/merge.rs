@@ -0,0 +1,159 @@
+use crate::fast_forward::local_target_branch;
+use crate::model::{trial_merge, BranchDelta, RepoBranchDeltas, TrialMerge};
+use git2::{Oid, Repository};
+
+/// merges HEAD into the given target branch in memory
+pub fn trial_merge_branch(
+    repo_deltas: &RepoBranchDeltas,
+    branch_delta: &BranchDelta,
+) -> Result<TrialMerge, String> {
+    let (head_id, target_id) = ids(repo_deltas, branch_delta)?;
+    let git_repo = Repository::open(&repo_deltas.repo.abs_path).map_err(|e| e.to_string())?;
+    trial_merge(&git_repo, head_id, target_id).map_err(|e| e.to_string())
+}
+
+/// merges HEAD into the local target branch by creating a merge commit on it;
+/// fails (without touching anything) if the merge isn't clean or the branch
+/// can't be moved (see `local_target_branch`)
+pub fn create_merge_commit(
+    repo_deltas: &RepoBranchDeltas,
+    branch_delta: &BranchDelta,
+) -> Result<Oid, String> {
+    let (head_id, target_id) = ids(repo_deltas, branch_delta)?;
+    let git_repo = Repository::open(&repo_deltas.repo.abs_path).map_err(|e| e.to_string())?;
+    let branch = local_target_branch(&git_repo, branch_delta)?;
+    let ref_name = branch
+        .get()
+        .name()
+        .ok_or_else(|| String::from("branch name is not valid utf-8"))?
+        .to_string();
+
+    let create = || -> Result<Oid, git2::Error> {
+        let head_commit = git_repo.find_commit(head_id)?;
+        let target_commit = git_repo.find_commit(target_id)?;
+        let mut index = git_repo.merge_commits(&target_commit, &head_commit, None)?;
+        if index.has_conflicts() {
+            return Err(git2::Error::from_str("merge is not clean"));
+        }
+        let tree = git_repo.find_tree(index.write_tree_to(&git_repo)?)?;
+        let signature = git_repo.signature()?;
+        let message = format!(
+            "Merge commit '{}' into {}",
+            head_id, branch_delta.branch_name
+        );
+        git_repo.commit(
+            Some(&ref_name),
+            &signature,
+            &signature,
+            &message,
+            &tree,
+            &[&target_commit, &head_commit],
+        )
+    };
+    create().map_err(|e| e.message().to_string())
+}
+
+fn ids(repo_deltas: &RepoBranchDeltas, branch_delta: &BranchDelta) -> Result<(Oid, Oid), String> {
+    match (repo_deltas.head_id, branch_delta.target_id) {
+        (Some(head_id), Some(target_id)) => Ok((head_id, target_id)),
+        _ => Err(String::from("branch not found")),
+    }
+}
+
+/// creates a repo where HEAD and "release" changed the given files
+/// since their merge-base and scans it against the given base
+#[cfg(test)]
+fn test_model(
+    name: &str,
+    base: &str,
+    head_file: &str,
+    release_file: &str,
+) -> (Repository, RepoBranchDeltas) {
+    use crate::model::{
+        calc_branch_deltas_for_a_single_repo, create_synthetic_repo, synthetic_commit, Repo,
+    };
+    use std::sync::Arc;
+
+    let path = std::env::temp_dir().join(format!("oper-delta-merge-{}", name));
+    let _ = std::fs::remove_dir_all(&path);
+    let git_repo = create_synthetic_repo(&path, 1, 0, 0).unwrap();
+    let merge_base = git_repo.refname_to_id("refs/heads/main").unwrap();
+    let head = synthetic_commit(&git_repo, Some(merge_base), &[(head_file, "head\n")]).unwrap();
+    let release =
+        synthetic_commit(&git_repo, Some(merge_base), &[(release_file, "release\n")]).unwrap();
+    git_repo
+        .reference("refs/heads/main", head, true, "")
+        .unwrap();
+    git_repo
+        .reference("refs/heads/release", release, true, "")
+        .unwrap();
+
+    let repo = Arc::new(Repo::from(path, name.to_string()));
+    let repo_deltas = calc_branch_deltas_for_a_single_repo(&repo, base, &["release"]);
+    (git_repo, repo_deltas)
+}
+
+#[test]
+fn test_clean_merge() {
+    use crate::model::{calc_branch_deltas_for_a_single_repo, Delta};
+
+    let (git_repo, repo_deltas) = test_model("clean", "HEAD", "a", "b");
+    let branch_delta = &repo_deltas.deltas[0];
+    assert_eq!(branch_delta.delta, Delta::NotConsolidated);
+    assert_eq!(
+        trial_merge_branch(&repo_deltas, branch_delta),
+        Ok(TrialMerge { conflicts: vec![] })
+    );
+
+    let merge_id = create_merge_commit(&repo_deltas, branch_delta).unwrap();
+    assert_eq!(
+        git_repo.refname_to_id("refs/heads/release").unwrap(),
+        merge_id
+    );
+    let repo_deltas = calc_branch_deltas_for_a_single_repo(&repo_deltas.repo, "HEAD", &["release"]);
+    assert_eq!(
+        repo_deltas.deltas[0].delta,
+        Delta::ConsolidatedByMergeCommit
+    );
+
+    let _ = std::fs::remove_dir_all(git_repo.workdir().unwrap());
+}
+
+#[test]
+fn test_conflicting_merge() {
+    let (git_repo, repo_deltas) = test_model("conflict", "HEAD", "a", "a");
+    let branch_delta = &repo_deltas.deltas[0];
+    let release = git_repo.refname_to_id("refs/heads/release").unwrap();
+    assert_eq!(
+        trial_merge_branch(&repo_deltas, branch_delta),
+        Ok(TrialMerge {
+            conflicts: vec![String::from("a")]
+        })
+    );
+
+    assert!(create_merge_commit(&repo_deltas, branch_delta).is_err());
+    assert_eq!(
+        git_repo.refname_to_id("refs/heads/release").unwrap(),
+        release
+    );
+
+    let _ = std::fs::remove_dir_all(git_repo.workdir().unwrap());
+}
+
+#[test]
+fn test_merge_into_checked_out_branch() {
+    let (git_repo, repo_deltas) = test_model("checked-out", "main", "a", "b");
+    git_repo.set_head("refs/heads/release").unwrap();
+    let release = git_repo.refname_to_id("refs/heads/release").unwrap();
+
+    assert_eq!(
+        create_merge_commit(&repo_deltas, &repo_deltas.deltas[0]),
+        Err(String::from("branch is checked out"))
+    );
+    assert_eq!(
+        git_repo.refname_to_id("refs/heads/release").unwrap(),
+        release
+    );
+
+    let _ = std::fs::remove_dir_all(git_repo.workdir().unwrap());
+}
@@ -1,20 +1,42 @@
 use crate::utils::repos_paths_from;
+use chrono::{DateTime, Local, TimeZone};
 use console::style;
-use git2::{Branch, BranchType, Repository};
+use git2::{BranchType, Oid, Repository};
 use indicatif::{MultiProgress, ParallelProgressIterator, ProgressBar, ProgressStyle};
 use rayon::prelude::*;
+use std::collections::HashMap;
 use std::fmt;
 use std::fs::File;
 use std::path::PathBuf;
-use std::process::Command;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::thread;
 
+/// the default base target branches are compared against
+pub const DEFAULT_BASE: &str = "HEAD";
+
+/// prefix of the target which is resolved per repo to the revision pinned by a manifest
+/// (not a valid branch name, so it can't be mixed up with one)
+pub const PINNED_PREFIX: &str = "manifest:";
+
 /// representation of a local git repository
+#[derive(Clone)]
 pub struct Repo {
     pub abs_path: PathBuf,
     pub rel_path: String,
     pub description: String,
+    /// the revisions pinned by manifests, by target name (see `PINNED_PREFIX`);
+    /// empty if the repo isn't part of any of them
+    pub pinned: HashMap<String, PinnedRevision>,
+}
+
+/// the revision a manifest pins a repo to
+#[derive(Clone, Debug, PartialEq)]
+pub struct PinnedRevision {
+    /// a sha, a branch name or a full ref (e.g. refs/tags/v1.0)
+    pub revision: String,
+    /// the remote branch names are looked up in first
+    pub remote: Option<String>,
 }
 
 // a qualitative difference between two branches
@@ -23,6 +45,7 @@ pub enum Delta {
     ConsolidatedBySameCommit,
     ConsolidatedByMergeCommit,
     ConsolidatedByEqualContent,
+    ConsolidatedByCherryPick,
     NotConsolidatedButFastForwardable,
     NotConsolidated,
     BranchNotFound,
@@ -32,36 +55,163 @@ pub enum Delta {
 pub struct BranchDelta {
     pub branch_name: String,
     pub delta: Delta,
+    /// None if the branch hasn't been found
+    pub target_id: Option<Oid>,
+    /// None if the branch hasn't been found or there is no merge-base
+    pub merge_base_id: Option<Oid>,
     pub distance_head_to_merge_base: Result<u32, String>,
     pub distance_target_to_merge_base: Result<u32, String>,
+    pub commits_head_to_merge_base: Result<Vec<CommitInfo>, String>,
+    pub commits_target_to_merge_base: Result<Vec<CommitInfo>, String>,
+    /// None if no diff stats have been calculated (see `add_diff_stats`)
+    pub diff_stats: Option<BranchDiffStats>,
+    /// None if no trial merge has been performed
+    pub trial_merge: Option<Result<TrialMerge, String>>,
+}
+
+/// the size of the changes between two commits
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DiffStats {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// the size of the changes between HEAD, the target branch and their merge-base
+#[derive(Clone, Debug, PartialEq)]
+pub struct BranchDiffStats {
+    pub head_to_target: Result<DiffStats, String>,
+    pub merge_base_to_head: Result<DiffStats, String>,
+    pub merge_base_to_target: Result<DiffStats, String>,
+}
+
+/// the outcome of merging HEAD into a target branch in memory
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrialMerge {
+    /// paths of all files with conflicts, empty if the merge is clean
+    pub conflicts: Vec<String>,
+}
+
+/// a single commit found on one side of the merge-base
+#[derive(Clone, Debug)]
+pub struct CommitInfo {
+    pub id: Oid,
+    pub author: String,
+    pub time: DateTime<Local>,
+    pub summary: String,
+    /// true if an equivalent patch (same patch-id) exists on the other side
+    pub picked: bool,
+}
+
+/// a problem which prevented calculating the deltas of a repo
+#[derive(Clone, Debug, PartialEq)]
+pub enum RepoError {
+    OpenFailed(String),
+    UnbornHead,
+    InvalidHead(String),
+    InvalidBase(String),
+    CorruptObject(String),
 }
 
 #[derive(Clone)]
 pub struct RepoBranchDeltas {
     pub repo: Arc<Repo>,
+    /// the ref all target branches are compared against, HEAD by default
+    pub base: String,
+    /// the commit `base` resolves to, None if `error` is set
+    pub head_id: Option<Oid>,
+    /// empty if `error` is set
     pub deltas: Vec<BranchDelta>,
+    pub error: Option<RepoError>,
 }
 
+#[derive(Clone)]
 pub struct Filter {
     pub include_consolidated_by_same_commit: bool,
     pub include_consolidated_by_merge_commit: bool,
     pub include_consolidated_by_equal_content: bool,
+    pub include_consolidated_by_cherry_pick: bool,
     pub include_non_consolidated: bool,
     pub include_non_consolidated_but_ff_able: bool,
     pub include_branch_not_found: bool,
+    pub include_repo_errors: bool,
     pub repo_ignore_list: Option<String>,
 }
 
+impl Delta {
+    /// all variants, from "best" to "worst"
+    pub const ALL: [Delta; 7] = [
+        Delta::ConsolidatedBySameCommit,
+        Delta::ConsolidatedByMergeCommit,
+        Delta::ConsolidatedByEqualContent,
+        Delta::ConsolidatedByCherryPick,
+        Delta::NotConsolidatedButFastForwardable,
+        Delta::NotConsolidated,
+        Delta::BranchNotFound,
+    ];
+}
+
 impl fmt::Display for Delta {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", self)
     }
 }
 
+impl RepoError {
+    /// a short, stable name of the kind of error (e.g. used in reports)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RepoError::OpenFailed(_) => "OpenFailed",
+            RepoError::UnbornHead => "UnbornHead",
+            RepoError::InvalidHead(_) => "InvalidHead",
+            RepoError::InvalidBase(_) => "InvalidBase",
+            RepoError::CorruptObject(_) => "CorruptObject",
+        }
+    }
+}
+
+impl fmt::Display for DiffStats {
+    /// same as `git diff --shortstat`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} files changed, {} insertions(+), {} deletions(-)",
+            self.files_changed, self.insertions, self.deletions
+        )
+    }
+}
+
+impl fmt::Display for RepoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RepoError::OpenFailed(e) => write!(f, "Failed to open repository: {}", e),
+            RepoError::UnbornHead => write!(f, "HEAD points to a branch without any commit"),
+            RepoError::InvalidHead(e) => write!(f, "Failed to resolve HEAD: {}", e),
+            RepoError::InvalidBase(e) => write!(f, "Failed to resolve base: {}", e),
+            RepoError::CorruptObject(e) => write!(f, "Failed to read git object: {}", e),
+        }
+    }
+}
+
+impl FromStr for Delta {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Delta::ALL
+            .iter()
+            .find(|delta| delta.to_string() == s)
+            .cloned()
+            .ok_or_else(|| format!("unknown delta: {}", s))
+    }
+}
+
 pub fn create_model(
     repos: Vec<Arc<Repo>>,
+    base: &str,
     branches: Vec<&str>,
-    filter: Filter,
+    filter: &Filter,
+    diff_stats: bool,
+    trial_merge: bool,
 ) -> Result<Vec<RepoBranchDeltas>, std::io::Error> {
     // setup progress bar
     let progress = MultiProgress::new();
@@ -101,10 +251,11 @@ pub fn create_model(
     let repo_branch_deltas: Vec<RepoBranchDeltas> = repos
         .par_iter()
         .map(move |repo| {
-            let progress_bar = &progress_bars[rayon::current_thread_index()?];
+            //rayon may run an item on the calling thread, which isn't part of the pool
+            let progress_bar = &progress_bars[rayon::current_thread_index().unwrap_or(0)];
             progress_bar.set_message(&format!("Scanning {}", repo.rel_path));
 
-            let progress_error = |msg: &str, error: &dyn std::error::Error| {
+            let progress_error = |msg: &str, error: &dyn fmt::Display| {
                 progress_bar.println(format!(
                     "{}: {}: {}",
                     style(&msg).red(),
@@ -119,16 +270,22 @@ pub fn create_model(
                 return None;
             }
 
-            calc_branch_deltas_for_a_single_repo(repo, &branches, &filter).map_or_else(
-                |e| {
-                    progress_error("Failed to open", &e);
-                    None
-                },
-                |x| {
-                    progress_bar.set_message("Idle");
-                    x
-                },
-            )
+            let mut repo_branch_deltas =
+                calc_branch_deltas_for_a_single_repo(repo, base, &branches);
+            if diff_stats {
+                progress_bar.set_message(&format!("Calculating diff stats of {}", repo.rel_path));
+                add_diff_stats(&mut repo_branch_deltas);
+            }
+            if trial_merge {
+                progress_bar.set_message(&format!("Trial merging {}", repo.rel_path));
+                add_trial_merges(&mut repo_branch_deltas);
+            }
+            match &repo_branch_deltas.error {
+                Some(e) => progress_error("Failed to scan", e),
+                None => progress_bar.set_message("Idle"),
+            }
+
+            Some(repo_branch_deltas)
         })
         .progress_with(overall_progress)
         .filter_map(|x| x)
@@ -137,213 +294,494 @@ pub fn create_model(
     Ok(repo_branch_deltas)
 }
 
-fn calc_branch_deltas_for_a_single_repo(
+/// returns the names of the target branches (in the order given
+/// on the command line) as found in the model
+pub fn branch_names(model: &[RepoBranchDeltas]) -> Vec<String> {
+    model
+        .iter()
+        .find(|r| r.error.is_none())
+        .map(|r| r.deltas.iter().map(|b| b.branch_name.clone()).collect())
+        .unwrap_or_default()
+}
+
+/// the base all target branches have been compared against, HEAD if the model is empty
+pub fn base_name(model: &[RepoBranchDeltas]) -> String {
+    model
+        .first()
+        .map(|r| r.base.clone())
+        .unwrap_or_else(|| String::from(DEFAULT_BASE))
+}
+
+/// scans a single repo (e.g. again after it has been changed)
+pub fn calc_branch_deltas_for_a_single_repo(
     repo: &std::sync::Arc<Repo>,
+    base: &str,
     branches: &[&str],
-    filter: &Filter,
-) -> Result<Option<RepoBranchDeltas>, git2::Error> {
-    let git_repo = Repository::open(&repo.abs_path)?;
+) -> RepoBranchDeltas {
+    match calc_branch_deltas(repo, base, branches) {
+        Ok((head_id, deltas)) => RepoBranchDeltas {
+            repo: repo.clone(),
+            base: base.to_string(),
+            head_id: Some(head_id),
+            deltas,
+            error: None,
+        },
+        Err(error) => RepoBranchDeltas {
+            repo: repo.clone(),
+            base: base.to_string(),
+            head_id: None,
+            deltas: vec![],
+            error: Some(error),
+        },
+    }
+}
 
-    let head_as_obj = git_repo
-        .head()
-        .expect("No HEAD for git repo")
-        .peel(git2::ObjectType::Commit)
-        .unwrap();
+/// scans a repo again after it has been changed, including diff
+/// stats and trial merges if they have been calculated before
+pub fn rescan(repo_branch_deltas: &RepoBranchDeltas, branches: &[&str]) -> RepoBranchDeltas {
+    let mut rescanned = calc_branch_deltas_for_a_single_repo(
+        &repo_branch_deltas.repo,
+        &repo_branch_deltas.base,
+        branches,
+    );
+    if repo_branch_deltas
+        .deltas
+        .iter()
+        .any(|b| b.diff_stats.is_some())
+    {
+        add_diff_stats(&mut rescanned);
+    }
+    if repo_branch_deltas
+        .deltas
+        .iter()
+        .any(|b| b.trial_merge.is_some())
+    {
+        add_trial_merges(&mut rescanned);
+    }
+    rescanned
+}
+
+fn calc_branch_deltas(
+    repo: &Repo,
+    base: &str,
+    branches: &[&str],
+) -> Result<(Oid, Vec<BranchDelta>), RepoError> {
+    let git_repo = Repository::open(&repo.abs_path)
+        .map_err(|e| RepoError::OpenFailed(e.message().to_string()))?;
+
+    let head_as_obj = if base == DEFAULT_BASE {
+        git_repo
+            .head()
+            .map_err(|e| match e.code() {
+                git2::ErrorCode::UnbornBranch => RepoError::UnbornHead,
+                _ => RepoError::InvalidHead(e.message().to_string()),
+            })?
+            .peel(git2::ObjectType::Commit)
+            .map_err(|e| RepoError::InvalidHead(e.message().to_string()))?
+    } else if base.starts_with(PINNED_PREFIX) {
+        let pinned = repo
+            .pinned
+            .get(base)
+            .ok_or_else(|| RepoError::InvalidBase(format!("not part of {}", base)))?;
+        resolve_pinned_revision(&git_repo, pinned).ok_or_else(|| {
+            RepoError::InvalidBase(format!("revision '{}' not found", pinned.revision))
+        })?
+    } else {
+        git_repo
+            .revparse_single(base)
+            .and_then(|obj| obj.peel(git2::ObjectType::Commit))
+            .map_err(|e| RepoError::InvalidBase(e.message().to_string()))?
+    };
 
     let deltas = branches
         .iter()
-        .filter_map(|branch_name| {
+        .map(|branch_name| {
             let git_repo_ref = &git_repo;
-            let local_branch = git_repo_ref.find_branch(branch_name, BranchType::Local);
-            let branch = if local_branch.is_err() {
-                git_repo_ref.find_branch(branch_name, BranchType::Remote)
-            } else {
-                local_branch
-            };
-
-            let delta = match branch {
-                Ok(branch) => {
-                    let mut delta = Delta::NotConsolidated;
-                    if consolidated_by_same_commit(&head_as_obj, &branch) {
-                        delta = Delta::ConsolidatedBySameCommit;
-                    } else if consolidated_by_merge(git_repo_ref, &head_as_obj, &branch) {
-                        delta = Delta::ConsolidatedByMergeCommit;
-                    } else if consolidated_by_equal_content(git_repo_ref, &branch) {
-                        delta = Delta::ConsolidatedByEqualContent;
-                    } else if fast_forwardable(&repo, branch_name) {
-                        delta = Delta::NotConsolidatedButFastForwardable;
-                    }
-                    delta
+            match resolve_target(git_repo_ref, repo, branch_name) {
+                Some(target_as_obj) => {
+                    calc_branch_delta(git_repo_ref, &head_as_obj, branch_name, &target_as_obj)
+                        .map_err(|e| RepoError::CorruptObject(e.message().to_string()))
                 }
-                Err(_err) => Delta::BranchNotFound,
-            };
-
-            Some(BranchDelta {
-                branch_name: String::from(*branch_name),
-                delta: delta.clone(),
-                distance_head_to_merge_base: match delta {
-                    Delta::BranchNotFound => Ok(0),
-                    _ => calc_distance_head_to_merge_base(&repo, branch_name),
-                },
-                distance_target_to_merge_base: match delta {
-                    Delta::BranchNotFound => Ok(0),
-                    _ => calc_distance_target_to_merge_base(&repo, branch_name),
-                },
-            })
+                None => Ok(BranchDelta::not_found(branch_name)),
+            }
         })
-        .collect::<Vec<_>>();
+        .collect::<Result<_, _>>()?;
 
-    //apply filter from the command line
-    let include_repo = if filter.include_consolidated_by_same_commit
-        && deltas
-            .iter()
-            .any(|x| x.delta == Delta::ConsolidatedBySameCommit)
-    {
-        true
-    } else if filter.include_consolidated_by_merge_commit
-        && deltas
-            .iter()
-            .any(|x| x.delta == Delta::ConsolidatedByMergeCommit)
-    {
-        true
-    } else if filter.include_consolidated_by_equal_content
-        && deltas
-            .iter()
-            .any(|x| x.delta == Delta::ConsolidatedByEqualContent)
-    {
-        true
-    } else if filter.include_non_consolidated_but_ff_able
-        && deltas
+    Ok((head_as_obj.id(), deltas))
+}
+
+/// looks up the target branch as local branch first, then as remote branch;
+/// targets starting with `PINNED_PREFIX` are resolved to the pinned revision
+fn resolve_target<'r>(
+    git_repo: &'r Repository,
+    repo: &Repo,
+    branch_name: &str,
+) -> Option<git2::Object<'r>> {
+    if branch_name.starts_with(PINNED_PREFIX) {
+        return resolve_pinned_revision(git_repo, repo.pinned.get(branch_name)?);
+    }
+
+    git_repo
+        .find_branch(branch_name, BranchType::Local)
+        .or_else(|_| git_repo.find_branch(branch_name, BranchType::Remote))
+        .ok()?
+        .get()
+        .peel(git2::ObjectType::Commit)
+        .ok()
+}
+
+/// like repo does: a branch name refers to the branch of the remote (the local
+/// branch of the same name may have diverged), anything else is parsed as revision
+fn resolve_pinned_revision<'r>(
+    git_repo: &'r Repository,
+    pinned: &PinnedRevision,
+) -> Option<git2::Object<'r>> {
+    let remote_branch = pinned.remote.as_ref().map(|remote| {
+        let branch = pinned.revision.trim_start_matches("refs/heads/");
+        format!("refs/remotes/{}/{}", remote, branch)
+    });
+    remote_branch
+        .and_then(|remote_branch| git_repo.revparse_single(&remote_branch).ok())
+        .or_else(|| git_repo.revparse_single(&pinned.revision).ok())?
+        .peel(git2::ObjectType::Commit)
+        .ok()
+}
+
+impl Filter {
+    /// applies the filter from the command line
+    pub fn includes(&self, repo_branch_deltas: &RepoBranchDeltas) -> bool {
+        if repo_branch_deltas.error.is_some() {
+            return self.include_repo_errors;
+        }
+
+        let deltas = &repo_branch_deltas.deltas;
+        deltas
             .iter()
-            .any(|x| x.delta == Delta::NotConsolidatedButFastForwardable)
-    {
-        true
-    } else if filter.include_non_consolidated
-        && deltas.iter().any(|x| x.delta == Delta::NotConsolidated)
-    {
-        true
-    } else if filter.include_branch_not_found
-        && deltas.iter().all(|x| x.delta == Delta::BranchNotFound)
-    {
-        true
-    } else {
-        false
-    };
+            .any(|x| x.delta != Delta::BranchNotFound && self.includes_delta(&x.delta))
+            || (self.include_branch_not_found
+                && deltas.iter().all(|x| x.delta == Delta::BranchNotFound))
+    }
 
-    if include_repo {
-        Ok(Some(RepoBranchDeltas {
-            repo: repo.clone(),
-            deltas,
-        }))
-    } else {
-        Ok(None)
-    }
-}
-
-fn calc_distance_head_to_merge_base(repo: &Arc<Repo>, branch_name: &str) -> Result<u32, String> {
-    let output = Command::new("sh")
-        .current_dir(&repo.abs_path)
-        .arg("-c")
-        .arg(format!(
-            "git rev-list --count `git merge-base HEAD {}`..HEAD",
-            branch_name
-        ))
-        .output();
-
-    parse_git_numeric_output(output)
-}
-
-fn calc_distance_target_to_merge_base(repo: &Arc<Repo>, branch_name: &str) -> Result<u32, String> {
-    let output = Command::new("sh")
-        .current_dir(&repo.abs_path)
-        .arg("-c")
-        .arg(format!(
-            "git rev-list --count `git merge-base HEAD {}`..{}",
-            branch_name, branch_name
-        ))
-        .output();
-
-    parse_git_numeric_output(output)
-}
-
-fn parse_git_numeric_output(
-    output: std::result::Result<std::process::Output, std::io::Error>,
-) -> Result<u32, String> {
-    match output {
-        Ok(v) => {
-            let stdout = String::from_utf8_lossy(&v.stdout).trim().to_string();
-            stdout.parse::<u32>().or(Err(format!(
-                "Parsing git output failed: {} {}",
-                stdout,
-                String::from_utf8_lossy(&v.stderr)
-            )))
+    /// like `includes()` but only looks at the delta of the n-th target branch
+    pub fn includes_branch(&self, repo_branch_deltas: &RepoBranchDeltas, n: usize) -> bool {
+        if repo_branch_deltas.error.is_some() {
+            return self.include_repo_errors;
+        }
+
+        repo_branch_deltas
+            .deltas
+            .get(n)
+            .is_some_and(|x| self.includes_delta(&x.delta))
+    }
+
+    pub fn includes_delta(&self, delta: &Delta) -> bool {
+        match delta {
+            Delta::ConsolidatedBySameCommit => self.include_consolidated_by_same_commit,
+            Delta::ConsolidatedByMergeCommit => self.include_consolidated_by_merge_commit,
+            Delta::ConsolidatedByEqualContent => self.include_consolidated_by_equal_content,
+            Delta::ConsolidatedByCherryPick => self.include_consolidated_by_cherry_pick,
+            Delta::NotConsolidatedButFastForwardable => self.include_non_consolidated_but_ff_able,
+            Delta::NotConsolidated => self.include_non_consolidated,
+            Delta::BranchNotFound => self.include_branch_not_found,
         }
-        Err(e) => Err(e.to_string()),
+    }
+
+    pub fn set_includes_delta(&mut self, delta: &Delta, include: bool) {
+        let flag = match delta {
+            Delta::ConsolidatedBySameCommit => &mut self.include_consolidated_by_same_commit,
+            Delta::ConsolidatedByMergeCommit => &mut self.include_consolidated_by_merge_commit,
+            Delta::ConsolidatedByEqualContent => &mut self.include_consolidated_by_equal_content,
+            Delta::ConsolidatedByCherryPick => &mut self.include_consolidated_by_cherry_pick,
+            Delta::NotConsolidatedButFastForwardable => {
+                &mut self.include_non_consolidated_but_ff_able
+            }
+            Delta::NotConsolidated => &mut self.include_non_consolidated,
+            Delta::BranchNotFound => &mut self.include_branch_not_found,
+        };
+        *flag = include;
     }
 }
 
-fn consolidated_by_same_commit(head_as_obj: &git2::Object, branch: &Branch) -> bool {
-    let branch_as_obj = branch.get().peel(git2::ObjectType::Commit).unwrap();
-    head_as_obj.id() == branch_as_obj.id()
+fn calc_branch_delta(
+    git_repo: &Repository,
+    head_as_obj: &git2::Object,
+    branch_name: &str,
+    branch_as_obj: &git2::Object,
+) -> Result<BranchDelta, git2::Error> {
+    let head_id = head_as_obj.id();
+    let branch_id = branch_as_obj.id();
+    let merge_base = git_repo
+        .merge_base(head_id, branch_id)
+        .map_err(|e| format!("No merge-base found: {}", e.message()));
+
+    let distances = calc_distances_to_merge_base(git_repo, head_id, branch_id, &merge_base);
+    let mut commits_head = calc_commits_to_merge_base(git_repo, head_id, &merge_base);
+    let mut commits_target = calc_commits_to_merge_base(git_repo, branch_id, &merge_base);
+    if let (Ok(head_commits), Ok(target_commits)) = (&mut commits_head, &mut commits_target) {
+        mark_cherry_picks(git_repo, head_commits, target_commits);
+    }
+
+    let mut delta = Delta::NotConsolidated;
+    if head_id == branch_id {
+        delta = Delta::ConsolidatedBySameCommit;
+    } else if consolidated_by_merge(git_repo, head_as_obj, branch_as_obj)? {
+        delta = Delta::ConsolidatedByMergeCommit;
+    } else if equal_content(git_repo, head_id, branch_id).unwrap_or(false) {
+        delta = Delta::ConsolidatedByEqualContent;
+    } else if consolidated_by_cherry_pick(&commits_head) {
+        delta = Delta::ConsolidatedByCherryPick;
+    } else if fast_forwardable(git_repo, head_id, branch_id) {
+        delta = Delta::NotConsolidatedButFastForwardable;
+    }
+
+    Ok(BranchDelta {
+        branch_name: String::from(branch_name),
+        delta,
+        target_id: Some(branch_id),
+        merge_base_id: merge_base.as_ref().ok().cloned(),
+        distance_head_to_merge_base: distances.clone().map(|(head, _)| head),
+        distance_target_to_merge_base: distances.map(|(_, target)| target),
+        commits_head_to_merge_base: commits_head,
+        commits_target_to_merge_base: commits_target,
+        diff_stats: None,
+        trial_merge: None,
+    })
+}
+
+/// counts the commits of HEAD and of the target branch since their
+/// merge-base (same as `git rev-list --count $(git merge-base HEAD <branch>)..<x>`)
+fn calc_distances_to_merge_base(
+    git_repo: &Repository,
+    head_id: Oid,
+    branch_id: Oid,
+    merge_base: &Result<Oid, String>,
+) -> Result<(u32, u32), String> {
+    merge_base.clone()?;
+    let (ahead, behind) = git_repo
+        .graph_ahead_behind(head_id, branch_id)
+        .map_err(|e| e.to_string())?;
+    Ok((ahead as u32, behind as u32))
+}
+
+/// lists all commits reachable from `from` but not from
+/// the given merge-base (newest first)
+fn calc_commits_to_merge_base(
+    git_repo: &Repository,
+    from: Oid,
+    merge_base: &Result<Oid, String>,
+) -> Result<Vec<CommitInfo>, String> {
+    let merge_base = merge_base.clone()?;
+
+    let mut revwalk = git_repo.revwalk().map_err(|e| e.to_string())?;
+    revwalk.push(from).map_err(|e| e.to_string())?;
+    revwalk.hide(merge_base).map_err(|e| e.to_string())?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME);
+
+    revwalk
+        .map(|commit_id| {
+            commit_id
+                .and_then(|commit_id| git_repo.find_commit(commit_id))
+                .map(|commit| CommitInfo::from(&commit))
+                .map_err(|e| e.to_string())
+        })
+        .collect()
 }
 
 fn consolidated_by_merge(
     git_repo: &Repository,
     head_as_obj: &git2::Object,
-    branch: &Branch,
-) -> bool {
+    branch_as_obj: &git2::Object,
+) -> Result<bool, git2::Error> {
     //walk down the history of "branch" and probe for a commit which has HEAD as a parent
-    let branch_as_obj = branch.get().peel(git2::ObjectType::Commit).unwrap();
-    let mut revwalk = git_repo.revwalk().expect("Failed to create revwalk");
+    let mut revwalk = git_repo.revwalk()?;
 
-    revwalk
-        .push(branch_as_obj.id())
-        .expect("branch not found in revwalk");
+    revwalk.push(branch_as_obj.id())?;
     revwalk.simplify_first_parent();
     revwalk.set_sorting(git2::Sort::TIME);
 
     for commit_id in revwalk {
-        let commit = commit_id
-            .and_then(|commit_id| git_repo.find_commit(commit_id))
-            .expect("Failed to find commit");
+        let commit = git_repo.find_commit(commit_id?)?;
         if commit.parent_ids().any(|x| x == head_as_obj.id()) {
-            return true;
+            return Ok(true);
         }
     }
 
-    false
+    Ok(false)
 }
 
-fn consolidated_by_equal_content(git_repo: &Repository, branch: &Branch) -> bool {
-    let head_as_tree_obj = git_repo
-        .head()
-        .expect("No HEAD for git repo")
-        .peel(git2::ObjectType::Tree)
-        .unwrap();
-    let branch_as_tree_obj = branch.get().peel(git2::ObjectType::Tree).unwrap();
+/// true if both commits have the same content (same as `git diff --quiet <from> <to>`);
+/// cheaper than `calc_diff_stats()` as no file content is compared
+fn equal_content(git_repo: &Repository, from: Oid, to: Oid) -> Result<bool, git2::Error> {
+    let from_tree = git_repo.find_commit(from)?.tree()?;
+    let to_tree = git_repo.find_commit(to)?.tree()?;
+    let diff = git_repo.diff_tree_to_tree(Some(&from_tree), Some(&to_tree), None)?;
+    Ok(diff.deltas().len() == 0)
+}
 
-    git_repo
-        .diff_tree_to_tree(
-            head_as_tree_obj.as_tree(),
-            branch_as_tree_obj.as_tree(),
-            None,
-        )
-        .unwrap()
-        .deltas()
-        .count()
-        == 0
+/// calculates the diff stats between HEAD, every target branch which has
+/// been found and their merge-base (a full diff each, so it's done on demand)
+pub fn add_diff_stats(repo_branch_deltas: &mut RepoBranchDeltas) {
+    let head_id = match repo_branch_deltas.head_id {
+        Some(head_id) => head_id,
+        None => return,
+    };
+    let git_repo =
+        Repository::open(&repo_branch_deltas.repo.abs_path).map_err(|e| e.message().to_string());
+    let diff = |from: Option<Oid>, to: Oid| {
+        let from = from.ok_or_else(|| String::from("No merge-base found"))?;
+        let git_repo = git_repo.as_ref().map_err(|e| e.clone())?;
+        calc_diff_stats(git_repo, from, to).map_err(|e| e.to_string())
+    };
+    for branch_delta in repo_branch_deltas.deltas.iter_mut() {
+        if let Some(branch_id) = branch_delta.target_id {
+            branch_delta.diff_stats = Some(BranchDiffStats {
+                head_to_target: diff(Some(head_id), branch_id),
+                merge_base_to_head: diff(branch_delta.merge_base_id, head_id),
+                merge_base_to_target: diff(branch_delta.merge_base_id, branch_id),
+            });
+        }
+    }
+}
+
+/// counts the changes between two commits (same as `git diff --shortstat <from> <to>`)
+fn calc_diff_stats(git_repo: &Repository, from: Oid, to: Oid) -> Result<DiffStats, git2::Error> {
+    let from_tree = git_repo.find_commit(from)?.tree()?;
+    let to_tree = git_repo.find_commit(to)?.tree()?;
+    let stats = git_repo
+        .diff_tree_to_tree(Some(&from_tree), Some(&to_tree), None)?
+        .stats()?;
+
+    Ok(DiffStats {
+        files_changed: stats.files_changed(),
+        insertions: stats.insertions(),
+        deletions: stats.deletions(),
+    })
+}
+
+fn consolidated_by_cherry_pick(commits_head: &Result<Vec<CommitInfo>, String>) -> bool {
+    match commits_head {
+        Ok(commits) => !commits.is_empty() && commits.iter().all(|c| c.picked),
+        Err(_) => false,
+    }
+}
+
+/// flags all commits on either side of the merge-base which have
+/// an equivalent patch (by patch-id) on the other side
+fn mark_cherry_picks(
+    git_repo: &Repository,
+    head_commits: &mut [CommitInfo],
+    target_commits: &mut [CommitInfo],
+) {
+    if head_commits.is_empty() || target_commits.is_empty() {
+        return;
+    }
+
+    let head_patch_ids = head_commits
+        .iter()
+        .map(|c| patch_id(git_repo, c.id))
+        .collect::<Vec<_>>();
+    let target_patch_ids = target_commits
+        .iter()
+        .map(|c| patch_id(git_repo, c.id))
+        .collect::<Vec<_>>();
+
+    let mark = |commits: &mut [CommitInfo], own: &[Option<Oid>], other: &[Option<Oid>]| {
+        for (commit, patch_id) in commits.iter_mut().zip(own) {
+            commit.picked = patch_id.is_some() && other.contains(patch_id);
+        }
+    };
+    mark(head_commits, &head_patch_ids, &target_patch_ids);
+    mark(target_commits, &target_patch_ids, &head_patch_ids);
+}
+
+/// calculates a stable id for the changes introduced by a commit,
+/// ignoring whitespace, line numbers and the commit's position
+/// in history (similar to `git patch-id`); merge commits have none
+fn patch_id(git_repo: &Repository, commit_id: Oid) -> Option<Oid> {
+    let commit = git_repo.find_commit(commit_id).ok()?;
+    if commit.parent_count() > 1 {
+        return None;
+    }
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree().ok()?),
+        Err(_) => None,
+    };
+    let diff = git_repo
+        .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree().ok()?), None)
+        .ok()?;
+
+    let mut patch = Vec::<u8>::new();
+    diff.print(git2::DiffFormat::Patch, |delta, _hunk, line| {
+        match line.origin() {
+            'F' => {
+                for file in &[delta.old_file(), delta.new_file()] {
+                    patch.extend(file.path_bytes().unwrap_or_default());
+                    patch.push(b'\n');
+                }
+            }
+            origin @ '+' | origin @ '-' => {
+                patch.push(origin as u8);
+                patch.extend(line.content().iter().filter(|b| !b.is_ascii_whitespace()));
+                patch.push(b'\n');
+            }
+            _ => {}
+        }
+        true
+    })
+    .ok()?;
+
+    Oid::hash_object(git2::ObjectType::Blob, &patch).ok()
+}
+
+/// performs a trial merge of HEAD into every target branch which has been found
+pub fn add_trial_merges(repo_branch_deltas: &mut RepoBranchDeltas) {
+    let head_id = match repo_branch_deltas.head_id {
+        Some(head_id) => head_id,
+        None => return,
+    };
+    let git_repo = Repository::open(&repo_branch_deltas.repo.abs_path);
+    for branch_delta in repo_branch_deltas.deltas.iter_mut() {
+        if let Some(branch_id) = branch_delta.target_id {
+            branch_delta.trial_merge = Some(match &git_repo {
+                Ok(git_repo) => {
+                    trial_merge(git_repo, head_id, branch_id).map_err(|e| e.message().to_string())
+                }
+                Err(e) => Err(e.message().to_string()),
+            });
+        }
+    }
+}
+
+/// merges HEAD into the target branch in memory, nothing is written to the repo
+pub fn trial_merge(
+    git_repo: &Repository,
+    head_id: Oid,
+    branch_id: Oid,
+) -> Result<TrialMerge, git2::Error> {
+    let index = git_repo.merge_commits(
+        &git_repo.find_commit(branch_id)?,
+        &git_repo.find_commit(head_id)?,
+        None,
+    )?;
+
+    let mut conflicts = Vec::<String>::new();
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+            let path = String::from_utf8_lossy(&entry.path).to_string();
+            if !conflicts.contains(&path) {
+                conflicts.push(path);
+            }
+        }
+    }
+    Ok(TrialMerge { conflicts })
 }
 
-fn fast_forwardable(repo: &Arc<Repo>, branch_name: &str) -> bool {
-    Command::new("git")
-        .current_dir(&repo.abs_path)
-        .arg("merge-base")
-        .arg("--is-ancestor")
-        .arg("HEAD")
-        .arg(branch_name)
-        .status()
-        .expect("Failed to execute git-show command. git not installed?")
-        .success()
+/// true if the branch can be fast-forwarded to HEAD
+fn fast_forwardable(git_repo: &Repository, head_id: Oid, branch_id: Oid) -> bool {
+    git_repo
+        .graph_descendant_of(head_id, branch_id)
+        .unwrap_or(false)
 }
 
 impl Repo {
@@ -353,6 +791,350 @@ impl Repo {
             abs_path,
             rel_path,
             description,
+            pinned: HashMap::new(),
+        }
+    }
+
+    pub fn pinned_to(mut self, target: &str, pinned: PinnedRevision) -> Repo {
+        self.pinned.insert(target.to_string(), pinned);
+        self
+    }
+}
+
+impl BranchDelta {
+    /// the delta of a target branch which hasn't been found in the repo
+    pub fn not_found(branch_name: &str) -> BranchDelta {
+        BranchDelta {
+            branch_name: String::from(branch_name),
+            delta: Delta::BranchNotFound,
+            target_id: None,
+            merge_base_id: None,
+            distance_head_to_merge_base: Ok(0),
+            distance_target_to_merge_base: Ok(0),
+            commits_head_to_merge_base: Ok(vec![]),
+            commits_target_to_merge_base: Ok(vec![]),
+            diff_stats: None,
+            trial_merge: None,
+        }
+    }
+
+    /// a branch delta with the given delta for tests, all other fields are empty
+    #[cfg(test)]
+    pub fn with_delta(branch_name: &str, delta: Delta) -> BranchDelta {
+        BranchDelta {
+            delta,
+            ..BranchDelta::not_found(branch_name)
+        }
+    }
+}
+
+#[cfg(test)]
+impl RepoBranchDeltas {
+    /// a scanned repo at `path` (relative to the workspace "/ws") with the given
+    /// branch deltas for tests
+    pub fn with_deltas(path: &str, deltas: Vec<BranchDelta>) -> RepoBranchDeltas {
+        RepoBranchDeltas {
+            repo: Arc::new(Repo::from(
+                std::path::Path::new("/ws").join(path),
+                path.to_string(),
+            )),
+            base: String::from("HEAD"),
+            head_id: None,
+            deltas,
+            error: None,
+        }
+    }
+}
+
+impl CommitInfo {
+    pub fn from(commit: &git2::Commit) -> CommitInfo {
+        CommitInfo {
+            id: commit.id(),
+            author: commit.author().name().unwrap_or("<unknown>").to_string(),
+            time: Local.timestamp(commit.time().seconds(), 0),
+            summary: commit.summary().unwrap_or("").to_string(),
+            picked: false,
         }
     }
 }
+
+/// creates a repo with HEAD on "main" and a "release" branch, both based on
+/// `nr_of_shared_commits`; every commit changes the content of "file"
+#[cfg(test)]
+pub fn create_synthetic_repo(
+    path: &std::path::Path,
+    nr_of_shared_commits: usize,
+    nr_of_head_commits: usize,
+    nr_of_target_commits: usize,
+) -> Result<Repository, git2::Error> {
+    let git_repo = Repository::init(path)?;
+    let mut config = git_repo.config()?;
+    config.set_str("user.name", "oper-delta")?;
+    config.set_str("user.email", "oper-delta@example.com")?;
+    let commit = |parent: Option<Oid>, content: String| {
+        synthetic_commit(&git_repo, parent, &[("file", &content)])
+    };
+
+    let mut merge_base = None;
+    for n in 0..nr_of_shared_commits {
+        merge_base = Some(commit(merge_base, format!("shared {}", n))?);
+    }
+    let mut head = merge_base;
+    for n in 0..nr_of_head_commits {
+        head = Some(commit(head, format!("head {}", n))?);
+    }
+    let mut target = merge_base;
+    for n in 0..nr_of_target_commits {
+        target = Some(commit(target, format!("target {}", n))?);
+    }
+
+    git_repo.reference("refs/heads/main", head.unwrap(), true, "")?;
+    git_repo.reference("refs/heads/release", target.unwrap(), true, "")?;
+    git_repo.set_head("refs/heads/main")?;
+    Ok(git_repo)
+}
+
+/// commits the given files (path, content) on top of the tree of `parent`
+/// without moving any ref; the contents are used as commit message
+#[cfg(test)]
+pub fn synthetic_commit(
+    git_repo: &Repository,
+    parent: Option<Oid>,
+    files: &[(&str, &str)],
+) -> Result<Oid, git2::Error> {
+    let parent = parent.map(|id| git_repo.find_commit(id)).transpose()?;
+    let parent_tree = parent.as_ref().map(|p| p.tree()).transpose()?;
+    let mut tree_builder = git_repo.treebuilder(parent_tree.as_ref())?;
+    for (file, content) in files {
+        tree_builder.insert(file, git_repo.blob(content.as_bytes())?, 0o100_644)?;
+    }
+    let tree = git_repo.find_tree(tree_builder.write()?)?;
+    let sig = git_repo.signature()?;
+    let message = files
+        .iter()
+        .map(|(_, c)| c.trim())
+        .collect::<Vec<_>>()
+        .join(", ");
+    git_repo.commit(
+        None,
+        &sig,
+        &sig,
+        &message,
+        &tree,
+        &parent.iter().collect::<Vec<_>>(),
+    )
+}
+
+#[test]
+fn test_diff_stats() {
+    let path = std::env::temp_dir().join("oper-delta-diff-stats");
+    let _ = std::fs::remove_dir_all(&path);
+    let git_repo = create_synthetic_repo(&path, 1, 2, 0).unwrap();
+    let repo = Arc::new(Repo::from(path.clone(), String::from("diff-stats")));
+
+    let mut repo_deltas = calc_branch_deltas_for_a_single_repo(&repo, "HEAD", &["release"]);
+    assert_eq!(repo_deltas.deltas[0].diff_stats, None);
+    add_diff_stats(&mut repo_deltas);
+    let diff_stats = repo_deltas.deltas[0].diff_stats.clone().unwrap();
+    let one_line_changed = DiffStats {
+        files_changed: 1,
+        insertions: 1,
+        deletions: 1,
+    };
+    assert_eq!(diff_stats.head_to_target, Ok(one_line_changed.clone()));
+    assert_eq!(diff_stats.merge_base_to_head, Ok(one_line_changed));
+    assert_eq!(diff_stats.merge_base_to_target, Ok(DiffStats::default()));
+    assert_eq!(
+        diff_stats.head_to_target.unwrap().to_string(),
+        "1 files changed, 1 insertions(+), 1 deletions(-)"
+    );
+
+    //same content as HEAD, but another commit
+    let release = git_repo.refname_to_id("refs/heads/release").unwrap();
+    let equal = synthetic_commit(&git_repo, Some(release), &[("file", "head 1")]).unwrap();
+    git_repo
+        .reference("refs/heads/release", equal, true, "")
+        .unwrap();
+    let repo_deltas = calc_branch_deltas_for_a_single_repo(&repo, "HEAD", &["release"]);
+    assert_eq!(
+        repo_deltas.deltas[0].delta,
+        Delta::ConsolidatedByEqualContent
+    );
+
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+#[test]
+fn test_base() {
+    let path = std::env::temp_dir().join("oper-delta-base");
+    let _ = std::fs::remove_dir_all(&path);
+    create_synthetic_repo(&path, 1, 1, 2).unwrap();
+    let repo = Arc::new(Repo::from(path.clone(), String::from("base")));
+    let git_repo = Repository::open(&path).unwrap();
+    let main_id = git_repo.refname_to_id("refs/heads/main").unwrap();
+    let release_id = git_repo.refname_to_id("refs/heads/release").unwrap();
+
+    let repo_deltas = calc_branch_deltas_for_a_single_repo(&repo, "release", &["main"]);
+    assert_eq!(repo_deltas.base, "release");
+    assert_eq!(repo_deltas.head_id, Some(release_id));
+    let branch_delta = &repo_deltas.deltas[0];
+    assert_eq!(branch_delta.delta, Delta::NotConsolidated);
+    assert_eq!(branch_delta.distance_head_to_merge_base, Ok(2));
+    assert_eq!(branch_delta.distance_target_to_merge_base, Ok(1));
+
+    let repo_deltas = calc_branch_deltas_for_a_single_repo(&repo, &main_id.to_string(), &["main"]);
+    assert_eq!(repo_deltas.deltas[0].delta, Delta::ConsolidatedBySameCommit);
+
+    let repo_deltas = calc_branch_deltas_for_a_single_repo(&repo, "unknown", &["main"]);
+    assert_eq!(repo_deltas.error.map(|e| e.kind()), Some("InvalidBase"));
+
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+#[test]
+fn test_pinned_revision() {
+    let path = std::env::temp_dir().join("oper-delta-pinned");
+    let _ = std::fs::remove_dir_all(&path);
+    create_synthetic_repo(&path, 1, 1, 1).unwrap();
+    let git_repo = Repository::open(&path).unwrap();
+    let main_id = git_repo.refname_to_id("refs/heads/main").unwrap();
+    let release_id = git_repo.refname_to_id("refs/heads/release").unwrap();
+    //the remote branch differs from the local one of the same name
+    git_repo
+        .reference("refs/remotes/origin/release", main_id, true, "")
+        .unwrap();
+
+    let target = format!("{}default.xml", PINNED_PREFIX);
+    let scan = |pinned: Option<PinnedRevision>| {
+        let mut repo = Repo::from(path.clone(), String::from("pinned"));
+        if let Some(pinned) = pinned {
+            repo = repo.pinned_to(&target, pinned);
+        }
+        let repo = Arc::new(repo);
+        calc_branch_deltas_for_a_single_repo(&repo, "HEAD", &[&target]).deltas[0].clone()
+    };
+    let pinned = |revision: &str, remote: Option<&str>| {
+        Some(PinnedRevision {
+            revision: revision.to_string(),
+            remote: remote.map(|r| r.to_string()),
+        })
+    };
+
+    let branch_delta = scan(pinned("release", None));
+    assert_eq!(branch_delta.branch_name, target);
+    assert_eq!(branch_delta.target_id, Some(release_id));
+    assert_eq!(branch_delta.delta, Delta::NotConsolidated);
+    assert_eq!(
+        scan(pinned("refs/heads/release", Some("origin"))).delta,
+        Delta::ConsolidatedBySameCommit
+    );
+    assert_eq!(
+        scan(pinned(&release_id.to_string(), Some("origin"))).target_id,
+        Some(release_id)
+    );
+    assert_eq!(scan(pinned("unknown", None)).delta, Delta::BranchNotFound);
+    assert_eq!(scan(None).delta, Delta::BranchNotFound);
+
+    //a pinned revision as base
+    let repo = Arc::new(
+        Repo::from(path.clone(), String::from("pinned"))
+            .pinned_to(&target, pinned("release", None).unwrap()),
+    );
+    let repo_deltas = calc_branch_deltas_for_a_single_repo(&repo, &target, &["main"]);
+    assert_eq!(repo_deltas.head_id, Some(release_id));
+    assert_eq!(repo_deltas.deltas[0].target_id, Some(main_id));
+    let repo_deltas = calc_branch_deltas_for_a_single_repo(&repo, "manifest:other.xml", &["main"]);
+    assert_eq!(repo_deltas.error.map(|e| e.kind()), Some("InvalidBase"));
+
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+#[test]
+fn test_cherry_pick() {
+    let path = std::env::temp_dir().join("oper-delta-cherry-pick");
+    let _ = std::fs::remove_dir_all(&path);
+    let git_repo = create_synthetic_repo(&path, 1, 0, 0).unwrap();
+    let merge_base = git_repo.refname_to_id("refs/heads/main").unwrap();
+    let commit = |parent: Oid, file: &str, content: &str| {
+        synthetic_commit(&git_repo, Some(parent), &[(file, content)]).unwrap()
+    };
+    let head_1 = commit(merge_base, "a", "fn a() {}\n");
+    let head_2 = commit(head_1, "b", "fn b() {}\n");
+    git_repo
+        .reference("refs/heads/main", head_2, true, "")
+        .unwrap();
+    //the same changes, the first one with changed whitespace
+    let picked_1 = commit(merge_base, "a", "fn a()  {\t}\n");
+    let scan = |release: Oid| {
+        git_repo
+            .reference("refs/heads/release", release, true, "")
+            .unwrap();
+        let repo = Arc::new(Repo::from(path.clone(), String::from("cherry-pick")));
+        calc_branch_deltas_for_a_single_repo(&repo, "HEAD", &["release"]).deltas[0].clone()
+    };
+    let picked = |commits: &Result<Vec<CommitInfo>, String>| {
+        commits
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|c| (c.id, c.picked))
+            .collect::<Vec<_>>()
+    };
+
+    let picked_2 = commit(picked_1, "b", "fn b() {}\n");
+    let branch_delta = scan(picked_2);
+    assert_eq!(branch_delta.delta, Delta::ConsolidatedByCherryPick);
+    assert_eq!(
+        picked(&branch_delta.commits_head_to_merge_base),
+        [(head_2, true), (head_1, true)]
+    );
+    assert_eq!(
+        picked(&branch_delta.commits_target_to_merge_base),
+        [(picked_2, true), (picked_1, true)]
+    );
+
+    //the second change differs in content
+    let changed_2 = commit(picked_1, "b", "fn b() { todo!() }\n");
+    let branch_delta = scan(changed_2);
+    assert_eq!(branch_delta.delta, Delta::NotConsolidated);
+    assert_eq!(
+        picked(&branch_delta.commits_head_to_merge_base),
+        [(head_2, false), (head_1, true)]
+    );
+    assert_eq!(
+        picked(&branch_delta.commits_target_to_merge_base),
+        [(changed_2, false), (picked_1, true)]
+    );
+
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+#[test]
+fn test_create_model() {
+    let path = std::env::temp_dir().join("oper-delta-create-model");
+    let _ = std::fs::remove_dir_all(&path);
+    create_synthetic_repo(&path, 1, 1, 0).unwrap();
+    let repo = Arc::new(Repo::from(path.clone(), String::from("single")));
+    let filter = Filter {
+        include_consolidated_by_same_commit: true,
+        include_consolidated_by_merge_commit: true,
+        include_consolidated_by_equal_content: true,
+        include_consolidated_by_cherry_pick: true,
+        include_non_consolidated: true,
+        include_non_consolidated_but_ff_able: true,
+        include_branch_not_found: true,
+        include_repo_errors: true,
+        repo_ignore_list: None,
+    };
+
+    //a single repo is likely to be scanned on the calling thread instead of the pool
+    let model = create_model(vec![repo], "HEAD", vec!["release"], &filter, false, false).unwrap();
+    assert_eq!(model.len(), 1);
+    assert_eq!(model[0].repo.rel_path, "single");
+    assert_eq!(
+        model[0].deltas[0].delta,
+        Delta::NotConsolidatedButFastForwardable
+    );
+
+    let _ = std::fs::remove_dir_all(&path);
+}
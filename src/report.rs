@@ -1,6 +1,11 @@
-use crate::model::{Delta, RepoBranchDeltas};
+use crate::model::{
+    base_name, branch_names, BranchDelta, BranchDiffStats, CommitInfo, Delta, DiffStats,
+    RepoBranchDeltas,
+};
 use anyhow::{anyhow, Result};
+use serde::Serialize;
 use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::path::Path;
 use std::vec::Vec;
 
@@ -8,26 +13,49 @@ use spsheet::ods;
 use spsheet::xlsx;
 use spsheet::{Book, Cell, Sheet};
 
-pub fn generate(model: Vec<RepoBranchDeltas>, output_file_path: &str) -> Result<()> {
-    if model.is_empty() {
-        return Err(anyhow!("No (unfiltered) repos left to write report about"));
-    }
+/// version of the json/ndjson report schema (see README.md); to be
+/// increased whenever a field is renamed, removed or changes its meaning
+pub const JSON_SCHEMA_VERSION: u32 = 1;
 
+const UNKNOWN_FORMAT: &str = "Couldn't derive report format from filename. Supported endings are: .csv, .ods, .xlsx, .json, .ndjson, .md, .html";
+
+pub fn generate(model: Vec<RepoBranchDeltas>, output_file_path: &str) -> Result<()> {
     let path = Path::new(output_file_path);
     let extension = path.extension().and_then(|s| s.to_str());
     if extension.is_none() {
-        return Err(anyhow!(
-            "Couldn't derive report format from filename. Supported endings are: .csv, .ods, .xlsx"
-        ));
+        return Err(anyhow!(UNKNOWN_FORMAT));
+    }
+
+    //machine-readable formats are fine with an empty model
+    match extension {
+        Some("json") => return generate_json(model, path),
+        Some("ndjson") => return generate_ndjson(model, path),
+        _ => {}
+    }
+
+    if model.is_empty() {
+        return Err(anyhow!("No (unfiltered) repos left to write report about"));
     }
 
     match extension {
         Some("csv") => generate_csv(model, path),
         Some("ods") => generate_ods(model, path),
         Some("xlsx") => generate_xlsx(model, path),
-        _ => Err(anyhow!(
-            "Couldn't derive report format from filename. Supported endings are: .csv, .ods, .xlsx"
-        )),
+        Some("md") => generate_markdown(model, path),
+        Some("html") => generate_html(model, path),
+        _ => Err(anyhow!(UNKNOWN_FORMAT)),
+    }
+}
+
+/// writes the report in the given machine-readable format ("json" or
+/// "ndjson") to stdout
+pub fn print(model: Vec<RepoBranchDeltas>, format: &str) -> Result<()> {
+    let stdout = std::io::stdout();
+    let writer = stdout.lock();
+    match format {
+        "json" => write_json(&model, writer),
+        "ndjson" => write_ndjson(&model, writer),
+        _ => Err(anyhow!("Unsupported output format: {}", format)),
     }
 }
 
@@ -43,7 +71,7 @@ struct CommaSeperatedSpreadsheet {
 impl CommaSeperatedSpreadsheet {
     pub fn new(output_file_path: &Path) -> Result<Self> {
         Ok(CommaSeperatedSpreadsheet {
-            writer: csv::Writer::from_path(&output_file_path)?,
+            writer: csv::Writer::from_path(output_file_path)?,
         })
     }
 
@@ -93,6 +121,160 @@ impl SpreadSheetBuilder for OdsXlsxSpreadsheet {
     }
 }
 
+/// builder for human-readable documents (like release notes or wiki pages)
+trait DocumentBuilder {
+    fn add_heading(&mut self, level: usize, text: &str) -> Result<()>;
+    fn add_paragraph(&mut self, text: &str) -> Result<()>;
+    fn start_table(&mut self, header: &[String]) -> Result<()>;
+    fn add_row(&mut self, cells: &[DocumentCell], color: Option<&str>) -> Result<()>;
+    fn finish_table(&mut self) -> Result<()>;
+}
+
+enum DocumentCell {
+    Text(String),
+    Link(String, String),
+}
+
+struct MarkdownDocument {
+    content: String,
+}
+
+impl MarkdownDocument {
+    pub fn new() -> Self {
+        MarkdownDocument {
+            content: String::new(),
+        }
+    }
+
+    fn escape(text: &str) -> String {
+        text.replace('|', "\\|")
+    }
+
+    fn color_to_marker(color: &str) -> &'static str {
+        match color {
+            "green" => "🟢",
+            "orange" => "🟡",
+            "red" => "🔴",
+            _ => "🔵",
+        }
+    }
+}
+
+impl DocumentBuilder for MarkdownDocument {
+    fn add_heading(&mut self, level: usize, text: &str) -> Result<()> {
+        self.content += &format!("{} {}\n\n", "#".repeat(level), text);
+        Ok(())
+    }
+
+    fn add_paragraph(&mut self, text: &str) -> Result<()> {
+        self.content += &format!("{}\n\n", text);
+        Ok(())
+    }
+
+    fn start_table(&mut self, header: &[String]) -> Result<()> {
+        let header = header.iter().map(|h| Self::escape(h)).collect::<Vec<_>>();
+        self.content += &format!("| | {} |\n", header.join(" | "));
+        self.content += &format!("|-|{}\n", "-|".repeat(header.len()));
+        Ok(())
+    }
+
+    fn add_row(&mut self, cells: &[DocumentCell], color: Option<&str>) -> Result<()> {
+        let cells = cells
+            .iter()
+            .map(|cell| match cell {
+                DocumentCell::Text(text) => Self::escape(text),
+                DocumentCell::Link(text, url) => format!("[{}]({})", Self::escape(text), url),
+            })
+            .collect::<Vec<_>>();
+        self.content += &format!(
+            "| {} | {} |\n",
+            color.map(Self::color_to_marker).unwrap_or(""),
+            cells.join(" | ")
+        );
+        Ok(())
+    }
+
+    fn finish_table(&mut self) -> Result<()> {
+        self.content += "\n";
+        Ok(())
+    }
+}
+
+struct HtmlDocument {
+    content: String,
+}
+
+impl HtmlDocument {
+    pub fn new() -> Self {
+        HtmlDocument {
+            content: String::from(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>oper-delta report</title>\n</head>\n<body>\n",
+            ),
+        }
+    }
+
+    pub fn finish(mut self) -> String {
+        self.content += "</body>\n</html>\n";
+        self.content
+    }
+
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+}
+
+impl DocumentBuilder for HtmlDocument {
+    fn add_heading(&mut self, level: usize, text: &str) -> Result<()> {
+        self.content += &format!("<h{0}>{1}</h{0}>\n", level, Self::escape(text));
+        Ok(())
+    }
+
+    fn add_paragraph(&mut self, text: &str) -> Result<()> {
+        self.content += &format!("<p>{}</p>\n", Self::escape(text));
+        Ok(())
+    }
+
+    fn start_table(&mut self, header: &[String]) -> Result<()> {
+        self.content += "<table border=\"1\" cellspacing=\"0\" cellpadding=\"4\">\n<tr>";
+        for h in header {
+            self.content += &format!("<th>{}</th>", Self::escape(h));
+        }
+        self.content += "</tr>\n";
+        Ok(())
+    }
+
+    fn add_row(&mut self, cells: &[DocumentCell], color: Option<&str>) -> Result<()> {
+        match color {
+            Some(color) => self.content += &format!("<tr style=\"color: {}\">", color),
+            None => self.content += "<tr>",
+        }
+        for cell in cells {
+            match cell {
+                DocumentCell::Text(text) => {
+                    self.content += &format!("<td>{}</td>", Self::escape(text))
+                }
+                DocumentCell::Link(text, url) => {
+                    self.content += &format!(
+                        "<td><a href=\"{}\">{}</a></td>",
+                        Self::escape(url),
+                        Self::escape(text)
+                    )
+                }
+            }
+        }
+        self.content += "</tr>\n";
+        Ok(())
+    }
+
+    fn finish_table(&mut self) -> Result<()> {
+        self.content += "</table>\n";
+        Ok(())
+    }
+}
+
 fn generate_ods(model: Vec<RepoBranchDeltas>, output_file_path: &Path) -> Result<()> {
     let mut spreadsheet = OdsXlsxSpreadsheet::new()?;
 
@@ -144,30 +326,260 @@ fn generate_csv(model: Vec<RepoBranchDeltas>, output_file_path: &Path) -> Result
     Ok(())
 }
 
+fn generate_markdown(model: Vec<RepoBranchDeltas>, output_file_path: &Path) -> Result<()> {
+    let mut document = MarkdownDocument::new();
+
+    model_into_document(&model, &mut document)?;
+
+    std::fs::write(output_file_path, document.content)?;
+
+    println!(
+        "Wrote {} records as Markdown to {}",
+        model.len(),
+        output_file_path.display()
+    );
+    Ok(())
+}
+
+fn generate_html(model: Vec<RepoBranchDeltas>, output_file_path: &Path) -> Result<()> {
+    let mut document = HtmlDocument::new();
+
+    model_into_document(&model, &mut document)?;
+
+    std::fs::write(output_file_path, document.finish())?;
+
+    println!(
+        "Wrote {} records as HTML to {}",
+        model.len(),
+        output_file_path.display()
+    );
+    Ok(())
+}
+
+fn generate_json(model: Vec<RepoBranchDeltas>, output_file_path: &Path) -> Result<()> {
+    write_json(&model, BufWriter::new(File::create(output_file_path)?))?;
+
+    println!(
+        "Wrote {} records as JSON to {}",
+        model.len(),
+        output_file_path.display()
+    );
+    Ok(())
+}
+
+fn generate_ndjson(model: Vec<RepoBranchDeltas>, output_file_path: &Path) -> Result<()> {
+    write_ndjson(&model, BufWriter::new(File::create(output_file_path)?))?;
+
+    println!(
+        "Wrote {} records as newline-delimited JSON to {}",
+        model.len(),
+        output_file_path.display()
+    );
+    Ok(())
+}
+
+fn write_json(model: &[RepoBranchDeltas], mut writer: impl Write) -> Result<()> {
+    let report = JsonReport {
+        schema_version: JSON_SCHEMA_VERSION,
+        repos: model.iter().map(JsonRepo::from).collect(),
+    };
+    serde_json::to_writer_pretty(&mut writer, &report)?;
+    writeln!(writer)?;
+    Ok(writer.flush()?)
+}
+
+fn write_ndjson(model: &[RepoBranchDeltas], mut writer: impl Write) -> Result<()> {
+    for repo in model {
+        let record = JsonNdRecord {
+            schema_version: JSON_SCHEMA_VERSION,
+            repo: JsonRepo::from(repo),
+        };
+        serde_json::to_writer(&mut writer, &record)?;
+        writeln!(writer)?;
+    }
+    Ok(writer.flush()?)
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    schema_version: u32,
+    repos: Vec<JsonRepo<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonNdRecord<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    repo: JsonRepo<'a>,
+}
+
+#[derive(Serialize)]
+struct JsonRepo<'a> {
+    path: &'a str,
+    base: &'a str,
+    abs_path: String,
+    branches: Vec<JsonBranch<'a>>,
+    error: Option<JsonError>,
+}
+
+#[derive(Serialize)]
+struct JsonError {
+    kind: &'static str,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct JsonBranch<'a> {
+    branch: &'a str,
+    delta: String,
+    distance_head_to_merge_base: Option<u32>,
+    distance_target_to_merge_base: Option<u32>,
+    commits_head_to_merge_base: Option<Vec<JsonCommit<'a>>>,
+    commits_target_to_merge_base: Option<Vec<JsonCommit<'a>>>,
+    diff_head_to_target: Option<JsonDiffStats>,
+    diff_merge_base_to_head: Option<JsonDiffStats>,
+    diff_merge_base_to_target: Option<JsonDiffStats>,
+    errors: Vec<&'a str>,
+}
+
+#[derive(Serialize)]
+struct JsonDiffStats {
+    files_changed: usize,
+    insertions: usize,
+    deletions: usize,
+}
+
+#[derive(Serialize)]
+struct JsonCommit<'a> {
+    id: String,
+    author: &'a str,
+    time: String,
+    summary: &'a str,
+    picked: bool,
+}
+
+impl<'a> From<&'a RepoBranchDeltas> for JsonRepo<'a> {
+    fn from(repo: &'a RepoBranchDeltas) -> Self {
+        JsonRepo {
+            path: &repo.repo.rel_path,
+            base: &repo.base,
+            abs_path: repo.repo.abs_path.to_string_lossy().to_string(),
+            branches: repo.deltas.iter().map(JsonBranch::from).collect(),
+            error: repo.error.as_ref().map(|e| JsonError {
+                kind: e.kind(),
+                message: e.to_string(),
+            }),
+        }
+    }
+}
+
+impl<'a> From<&'a BranchDelta> for JsonBranch<'a> {
+    fn from(branch: &'a BranchDelta) -> Self {
+        let commits = |commits: &'a Result<Vec<CommitInfo>, String>| {
+            commits
+                .as_ref()
+                .ok()
+                .map(|commits| commits.iter().map(JsonCommit::from).collect())
+        };
+        let diff_stats = |diff: fn(&BranchDiffStats) -> &Result<DiffStats, String>| {
+            let diff_stats = diff(branch.diff_stats.as_ref()?).as_ref().ok()?;
+            Some(JsonDiffStats {
+                files_changed: diff_stats.files_changed,
+                insertions: diff_stats.insertions,
+                deletions: diff_stats.deletions,
+            })
+        };
+        let diff_error = |diff: fn(&BranchDiffStats) -> &Result<DiffStats, String>| {
+            diff(branch.diff_stats.as_ref()?).as_ref().err()
+        };
+        let errors = [
+            branch.distance_head_to_merge_base.as_ref().err(),
+            branch.distance_target_to_merge_base.as_ref().err(),
+            branch.commits_head_to_merge_base.as_ref().err(),
+            branch.commits_target_to_merge_base.as_ref().err(),
+            diff_error(|d| &d.head_to_target),
+            diff_error(|d| &d.merge_base_to_head),
+            diff_error(|d| &d.merge_base_to_target),
+        ];
+        let mut unique_errors = Vec::<&str>::new();
+        for error in errors.iter().filter_map(|e| e.map(|e| e.as_str())) {
+            if !unique_errors.contains(&error) {
+                unique_errors.push(error);
+            }
+        }
+
+        JsonBranch {
+            branch: &branch.branch_name,
+            delta: branch.delta.to_string(),
+            distance_head_to_merge_base: branch.distance_head_to_merge_base.clone().ok(),
+            distance_target_to_merge_base: branch.distance_target_to_merge_base.clone().ok(),
+            commits_head_to_merge_base: commits(&branch.commits_head_to_merge_base),
+            commits_target_to_merge_base: commits(&branch.commits_target_to_merge_base),
+            diff_head_to_target: diff_stats(|d| &d.head_to_target),
+            diff_merge_base_to_head: diff_stats(|d| &d.merge_base_to_head),
+            diff_merge_base_to_target: diff_stats(|d| &d.merge_base_to_target),
+            errors: unique_errors,
+        }
+    }
+}
+
+impl<'a> From<&'a CommitInfo> for JsonCommit<'a> {
+    fn from(commit: &'a CommitInfo) -> Self {
+        JsonCommit {
+            id: commit.id.to_string(),
+            author: &commit.author,
+            time: commit.time.to_rfc3339(),
+            summary: &commit.summary,
+            picked: commit.picked,
+        }
+    }
+}
+
 fn model_into_spreadsheet(
     model: &[RepoBranchDeltas],
     builder: &mut dyn SpreadSheetBuilder,
 ) -> Result<()> {
+    let branches = branch_names(model);
+    let base = base_name(model);
+    //the diff stats and trial merge columns are only added if they have been calculated
+    let with_diff_stats = model
+        .iter()
+        .any(|r| r.deltas.iter().any(|b| b.diff_stats.is_some()));
+    let with_trial_merge = model
+        .iter()
+        .any(|r| r.deltas.iter().any(|b| b.trial_merge.is_some()));
+    let header_cells =
+        |branch: &str| branch_header_cells(&base, branch, with_diff_stats, with_trial_merge);
+
     builder.add_cell("Local Path of Repo".to_string())?;
-    for branch in &model[0].deltas {
-        builder.add_cell(format!("{} Branch: Delta", &branch.branch_name))?;
-        builder.add_cell(format!(
-            "{} Branch: Distance of HEAD to merge-base",
-            &branch.branch_name
-        ))?;
-        builder.add_cell(format!(
-            "{} Branch: Distance of {} to merge-base",
-            &branch.branch_name, &branch.branch_name
-        ))?;
+    for branch in &branches {
+        for cell in header_cells(branch) {
+            builder.add_cell(cell)?;
+        }
     }
+    builder.add_cell("Error".to_string())?;
     builder.finish_row()?;
 
     for repo in model {
         builder.add_cell(repo.repo.rel_path.clone())?;
-        for branch in &repo.deltas {
-            builder.add_cell(delta_to_string(&branch.delta))?;
-            builder.add_cell(distance_to_string(&branch.distance_head_to_merge_base))?;
-            builder.add_cell(distance_to_string(&branch.distance_target_to_merge_base))?;
+        match &repo.error {
+            None => {
+                for branch in &repo.deltas {
+                    for cell in branch_cells(&repo.base, branch, with_diff_stats, with_trial_merge)
+                    {
+                        builder.add_cell(cell)?;
+                    }
+                }
+                builder.add_cell(String::new())?;
+            }
+            Some(error) => {
+                for branch in &branches {
+                    for _ in header_cells(branch) {
+                        builder.add_cell(String::new())?;
+                    }
+                }
+                builder.add_cell(error.to_string())?;
+            }
         }
         builder.finish_row()?;
     }
@@ -175,6 +587,191 @@ fn model_into_spreadsheet(
     Ok(())
 }
 
+fn branch_header_cells(
+    base: &str,
+    branch_name: &str,
+    with_diff_stats: bool,
+    with_trial_merge: bool,
+) -> Vec<String> {
+    let mut cells = vec![
+        format!("{} Branch: Delta", branch_name),
+        format!("{} Branch: Distance of {} to merge-base", branch_name, base),
+        format!(
+            "{} Branch: Distance of {} to merge-base",
+            branch_name, branch_name
+        ),
+        format!(
+            "{} Branch: Commits of {} already cherry-picked",
+            branch_name, base
+        ),
+    ];
+    if with_diff_stats {
+        for range in &[
+            format!("{}..{}", base, branch_name),
+            format!("merge-base..{}", base),
+            format!("merge-base..{}", branch_name),
+        ] {
+            cells.push(format!("{} Branch: Files changed {}", branch_name, range));
+            cells.push(format!("{} Branch: Insertions {}", branch_name, range));
+            cells.push(format!("{} Branch: Deletions {}", branch_name, range));
+        }
+    }
+    if with_trial_merge {
+        cells.push(format!("{} Branch: Merge would conflict", branch_name));
+        cells.push(format!("{} Branch: Conflicting files", branch_name));
+    }
+    cells
+}
+
+fn branch_cells(
+    base: &str,
+    branch: &BranchDelta,
+    with_diff_stats: bool,
+    with_trial_merge: bool,
+) -> Vec<String> {
+    let mut cells = vec![
+        delta_to_string(&branch.delta, base),
+        distance_to_string(&branch.distance_head_to_merge_base),
+        distance_to_string(&branch.distance_target_to_merge_base),
+        picked_to_string(&branch.commits_head_to_merge_base),
+    ];
+    if with_diff_stats {
+        match &branch.diff_stats {
+            Some(diff_stats) => {
+                for diff_stats in &[
+                    &diff_stats.head_to_target,
+                    &diff_stats.merge_base_to_head,
+                    &diff_stats.merge_base_to_target,
+                ] {
+                    cells.extend(diff_stats_to_strings(diff_stats));
+                }
+            }
+            None => cells.extend(vec![String::new(); 9]),
+        }
+    }
+    if with_trial_merge {
+        let (conflict, files) = match &branch.trial_merge {
+            Some(Ok(t)) if t.conflicts.is_empty() => ("no".to_string(), "0".to_string()),
+            Some(Ok(t)) => ("yes".to_string(), t.conflicts.len().to_string()),
+            Some(Err(e)) => (e.clone(), String::new()),
+            None => (String::new(), String::new()),
+        };
+        cells.push(conflict);
+        cells.push(files);
+    }
+    cells
+}
+
+fn model_into_document(
+    model: &[RepoBranchDeltas],
+    builder: &mut dyn DocumentBuilder,
+) -> Result<()> {
+    let branches = branch_names(model);
+    let base = base_name(model);
+    let failed_repos = model
+        .iter()
+        .filter(|r| r.error.is_some())
+        .collect::<Vec<_>>();
+
+    builder.add_heading(1, "oper-delta report")?;
+    builder.add_paragraph(&format!(
+        "Delta of {} against {} in {} repositories",
+        base,
+        branches.join(", "),
+        model.len()
+    ))?;
+
+    //summary: number of repos per delta and branch
+    builder.add_heading(2, "Summary")?;
+    let mut header = vec!["Delta".to_string()];
+    header.extend(branches.iter().cloned());
+    builder.start_table(&header)?;
+    for delta in Delta::ALL.iter() {
+        let mut cells = vec![DocumentCell::Text(delta_to_string(delta, &base))];
+        for (n, _) in branches.iter().enumerate() {
+            let count = model
+                .iter()
+                .filter(|r| r.deltas.get(n).is_some_and(|b| &b.delta == delta))
+                .count();
+            cells.push(DocumentCell::Text(count.to_string()));
+        }
+        builder.add_row(&cells, Some(delta_to_color(delta)))?;
+    }
+    if !failed_repos.is_empty() {
+        let mut cells = vec![DocumentCell::Text("failed to scan repo".to_string())];
+        for _ in &branches {
+            cells.push(DocumentCell::Text(failed_repos.len().to_string()));
+        }
+        builder.add_row(&cells, Some(ERROR_COLOR))?;
+    }
+    builder.finish_table()?;
+
+    //repos which couldn't be scanned
+    if !failed_repos.is_empty() {
+        builder.add_heading(2, &format!("Failed to scan ({})", failed_repos.len()))?;
+        builder.start_table(&["Local Path of Repo".to_string(), "Error".to_string()])?;
+        for repo in failed_repos {
+            builder.add_row(
+                &[
+                    repo_link(repo),
+                    DocumentCell::Text(repo.error.as_ref().unwrap().to_string()),
+                ],
+                Some(ERROR_COLOR),
+            )?;
+        }
+        builder.finish_table()?;
+    }
+
+    //details: repos grouped by delta, per branch
+    for (n, branch) in branches.iter().enumerate() {
+        builder.add_heading(2, &format!("Branch {}", branch))?;
+        for delta in Delta::ALL.iter() {
+            let repos = model
+                .iter()
+                .filter(|r| r.deltas.get(n).is_some_and(|b| &b.delta == delta))
+                .collect::<Vec<_>>();
+            if repos.is_empty() {
+                continue;
+            }
+
+            builder.add_heading(
+                3,
+                &format!("{} ({})", delta_to_string(delta, &base), repos.len()),
+            )?;
+            builder.start_table(&[
+                "Local Path of Repo".to_string(),
+                format!("Distance of {} to merge-base", base),
+                format!("Distance of {} to merge-base", branch),
+            ])?;
+            for repo in repos {
+                let branch_delta = &repo.deltas[n];
+                builder.add_row(
+                    &[
+                        repo_link(repo),
+                        DocumentCell::Text(distance_to_string(
+                            &branch_delta.distance_head_to_merge_base,
+                        )),
+                        DocumentCell::Text(distance_to_string(
+                            &branch_delta.distance_target_to_merge_base,
+                        )),
+                    ],
+                    Some(delta_to_color(delta)),
+                )?;
+            }
+            builder.finish_table()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn repo_link(repo: &RepoBranchDeltas) -> DocumentCell {
+    DocumentCell::Link(
+        repo.repo.rel_path.clone(),
+        format!("file://{}", repo.repo.abs_path.display()),
+    )
+}
+
 fn distance_to_string(distance: &Result<u32, String>) -> String {
     match distance {
         Ok(v) => v.to_string(),
@@ -182,18 +779,182 @@ fn distance_to_string(distance: &Result<u32, String>) -> String {
     }
 }
 
-fn delta_to_string(delta: &Delta) -> String {
+/// files changed, insertions and deletions - or the error and two empty cells
+fn diff_stats_to_strings(diff_stats: &Result<DiffStats, String>) -> Vec<String> {
+    match diff_stats {
+        Ok(d) => vec![
+            d.files_changed.to_string(),
+            d.insertions.to_string(),
+            d.deletions.to_string(),
+        ],
+        Err(e) => vec![e.clone(), String::new(), String::new()],
+    }
+}
+
+fn picked_to_string(commits: &Result<Vec<CommitInfo>, String>) -> String {
+    match commits {
+        Ok(v) => v.iter().filter(|c| c.picked).count().to_string(),
+        Err(e) => e.clone(),
+    }
+}
+
+const ERROR_COLOR: &str = "red";
+
+/// same colors as used in the UI (see `DeltaView`)
+fn delta_to_color(delta: &Delta) -> &'static str {
+    match delta {
+        Delta::ConsolidatedBySameCommit => "green",
+        Delta::ConsolidatedByMergeCommit => "green",
+        Delta::ConsolidatedByEqualContent => "green",
+        Delta::ConsolidatedByCherryPick => "green",
+        Delta::NotConsolidatedButFastForwardable => "orange",
+        Delta::NotConsolidated => "red",
+        Delta::BranchNotFound => "blue",
+    }
+}
+
+/// the description of the delta with "HEAD" replaced by the given base
+fn delta_to_string(delta: &Delta, base: &str) -> String {
     match delta {
         Delta::ConsolidatedBySameCommit => "HEAD consolidated: points to the same commit as HEAD",
         Delta::ConsolidatedByMergeCommit => "HEAD consolidated: contains merge commit from HEAD",
         Delta::ConsolidatedByEqualContent => {
             "HEAD consolidated: content same as HEAD (however history differs)"
         }
+        Delta::ConsolidatedByCherryPick => {
+            "HEAD consolidated: all commits of HEAD have been cherry-picked"
+        }
         Delta::NotConsolidatedButFastForwardable => {
             "HEAD not consolidated: can be fast forwarded to HEAD"
         }
         Delta::NotConsolidated => "HEAD not consolidated: and not fast forwardable",
         Delta::BranchNotFound => "branch not found",
     }
-    .to_string()
+    .replace("HEAD", base)
+}
+
+#[cfg(test)]
+fn test_model() -> Vec<RepoBranchDeltas> {
+    vec![RepoBranchDeltas::with_deltas(
+        "frameworks/base",
+        vec![
+            BranchDelta {
+                distance_head_to_merge_base: Ok(2),
+                distance_target_to_merge_base: Err("No merge-base found".to_string()),
+                commits_head_to_merge_base: Ok(vec![]),
+                commits_target_to_merge_base: Err("No merge-base found".to_string()),
+                diff_stats: Some(BranchDiffStats {
+                    head_to_target: Ok(DiffStats {
+                        files_changed: 3,
+                        insertions: 10,
+                        deletions: 2,
+                    }),
+                    merge_base_to_head: Err("No merge-base found".to_string()),
+                    merge_base_to_target: Err("No merge-base found".to_string()),
+                }),
+                ..BranchDelta::with_delta("release", Delta::NotConsolidated)
+            },
+            BranchDelta::not_found("develop"),
+        ],
+    )]
+}
+
+#[cfg(test)]
+impl SpreadSheetBuilder for Vec<Vec<String>> {
+    fn add_cell(&mut self, cell: String) -> Result<()> {
+        if self.is_empty() {
+            self.push(vec![]);
+        }
+        self.last_mut().unwrap().push(cell);
+        Ok(())
+    }
+
+    fn finish_row(&mut self) -> Result<()> {
+        self.push(vec![]);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_model_into_spreadsheet() {
+    use crate::model::TrialMerge;
+
+    let mut rows = Vec::<Vec<String>>::new();
+    model_into_spreadsheet(&test_model(), &mut rows).unwrap();
+    assert_eq!(rows[0].len(), 1 + 2 * 13 + 1);
+
+    let mut model = test_model();
+    model[0].deltas[0].diff_stats = None;
+    let mut rows = Vec::<Vec<String>>::new();
+    model_into_spreadsheet(&model, &mut rows).unwrap();
+    assert_eq!(rows[0].len(), 1 + 2 * 4 + 1);
+
+    let mut model = test_model();
+    model[0].deltas[0].trial_merge = Some(Ok(TrialMerge {
+        conflicts: vec!["a".to_string(), "b".to_string()],
+    }));
+    let mut rows = Vec::<Vec<String>>::new();
+    model_into_spreadsheet(&model, &mut rows).unwrap();
+    assert_eq!(rows[0].len(), 1 + 2 * 15 + 1);
+    assert_eq!(rows[0][5], "release Branch: Files changed HEAD..release");
+    assert_eq!(rows[1][5..8], ["3", "10", "2"]);
+    assert_eq!(rows[1][8..11], ["No merge-base found", "", ""]);
+    assert_eq!(rows[0][14], "release Branch: Merge would conflict");
+    assert_eq!(rows[1][14..16], ["yes", "2"]);
+    //not performed as the branch hasn't been found
+    assert_eq!(rows[1][29..31], ["", ""]);
+}
+
+#[test]
+fn test_write_json() {
+    let mut output = Vec::<u8>::new();
+    write_json(&test_model(), &mut output).unwrap();
+
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["schema_version"], JSON_SCHEMA_VERSION);
+    let branch = &json["repos"][0]["branches"][0];
+    assert_eq!(json["repos"][0]["path"], "frameworks/base");
+    assert_eq!(branch["delta"], "NotConsolidated");
+    assert_eq!(branch["distance_head_to_merge_base"], 2);
+    assert!(branch["distance_target_to_merge_base"].is_null());
+    assert_eq!(branch["errors"], serde_json::json!(["No merge-base found"]));
+    assert_eq!(branch["diff_head_to_target"]["insertions"], 10);
+    assert!(branch["diff_merge_base_to_head"].is_null());
+}
+
+#[test]
+fn test_write_ndjson() {
+    let mut output = Vec::<u8>::new();
+    write_ndjson(&test_model(), &mut output).unwrap();
+
+    let lines = String::from_utf8(output).unwrap();
+    let lines = lines.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 1);
+    let json: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(json["schema_version"], JSON_SCHEMA_VERSION);
+    assert_eq!(json["branches"][1]["delta"], "BranchNotFound");
+}
+
+#[test]
+fn test_model_into_markdown() {
+    let mut document = MarkdownDocument::new();
+    model_into_document(&test_model(), &mut document).unwrap();
+
+    assert!(document
+        .content
+        .contains("| 🔴 | HEAD not consolidated: and not fast forwardable | 1 | 0 |"));
+    assert!(document.content.contains("### branch not found (1)"));
+    assert!(document.content.contains(
+        "| 🔴 | [frameworks/base](file:///ws/frameworks/base) | 2 | No merge-base found |"
+    ));
+}
+
+#[test]
+fn test_model_into_html() {
+    let mut document = HtmlDocument::new();
+    model_into_document(&test_model(), &mut document).unwrap();
+    let html = document.finish();
+
+    assert!(html.contains("<tr style=\"color: red\"><td><a href=\"file:///ws/frameworks/base\">frameworks/base</a></td>"));
+    assert!(html.ends_with("</html>\n"));
 }
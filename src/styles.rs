@@ -23,4 +23,8 @@ lazy_static! {
         Color::Dark(BaseColor::Magenta),
         Color::Dark(BaseColor::Black),
     );
+    pub static ref SEARCH_MATCH: ColorStyle = ColorStyle::new(
+        Color::Dark(BaseColor::Black),
+        Color::Light(BaseColor::Yellow),
+    );
 }
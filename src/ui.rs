@@ -1,46 +1,167 @@
+use crate::batch::{self, BatchResult};
 use crate::config::Config;
 use crate::cursive::traits::View;
-use crate::model::RepoBranchDeltas;
-use crate::utils::execute_on_repo;
-use crate::views::{DeltaView, ReposView, SeperatorView};
+use crate::diff::{changed_files, file_diff};
+use crate::fast_forward::{describe, fast_forward};
+use crate::merge::{create_merge_commit, trial_merge_branch};
+use crate::model::{add_diff_stats, rescan, BranchDelta, Delta, Filter, RepoBranchDeltas};
+use crate::styles::{GREEN, RED};
+use crate::utils::{
+    execute_on_repo, execute_on_repo_and_capture, execute_on_repo_in_foreground, CommandContext,
+};
+use crate::views::{filter_dialog, DeltaView, DiffView, RepoFilter, ReposView, SeperatorView};
 use cursive::event::{Event, Key};
 use cursive::traits::Boxable;
 use cursive::traits::Identifiable;
-use cursive::views::{BoxView, ViewRef};
+use cursive::traits::Scrollable;
+use cursive::utils::markup::StyledString;
+use cursive::views::{BoxView, Dialog, EditView, OnEventView, TextView, ViewRef};
 use cursive::views::{LayerPosition, LinearLayout};
 use cursive::Cursive;
 use std::default::Default;
+use std::process::Output;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// keys of the builtin commands, which take precedence over custom commands
+const BUILTIN_KEYS: [char; 10] = ['q', 'f', '/', 'n', 'N', 'F', 'M', 'D', 'k', 'j'];
+
+/// state of the search started with '/'
+struct Search {
+    pattern: String,
+    /// row selected when the search has been started
+    origin_row: usize,
+}
 
 fn update(siv: &mut Cursive, index: usize, repo_deltas: &RepoBranchDeltas) {
+    let repo_deltas = with_diff_stats(siv, repo_deltas);
     let mut delta_view: ViewRef<DeltaView> = siv.find_id("deltaView").unwrap();
-    delta_view.set_repo_deltas(repo_deltas);
+    delta_view.set_repo_deltas(&repo_deltas);
 
     let mut repos_view: ViewRef<ReposView> = siv.find_id("mainView").unwrap();
     repos_view.update_status_bar(index as i32);
 }
 
-pub fn show(model: Vec<RepoBranchDeltas>, config: &Config, total_nr_of_repos: usize) {
-    let nr_of_filtered_repos = model.len();
-    let first_repo = if nr_of_filtered_repos > 0 {
-        Some(model.get(0).unwrap().clone())
-    } else {
-        None
+fn apply_filter(siv: &mut Cursive, filter: RepoFilter) {
+    let mut repos_view: ViewRef<ReposView> = siv.find_id("mainView").unwrap();
+    repos_view.set_filter(filter);
+    let selected_repo = repos_view.selected_repo_deltas();
+    drop(repos_view);
+
+    let selected_repo = selected_repo.map(|repo| with_diff_stats(siv, &repo));
+    let mut delta_view: ViewRef<DeltaView> = siv.find_id("deltaView").unwrap();
+    match selected_repo {
+        Some(repo) => delta_view.set_repo_deltas(&repo),
+        None => delta_view.clear(),
+    }
+}
+
+/// calculates the diff stats of the given repo on demand (unless they have been
+/// calculated already) and stores them in the model, so every repo is diffed once
+fn with_diff_stats(siv: &mut Cursive, repo_deltas: &RepoBranchDeltas) -> RepoBranchDeltas {
+    let missing = |b: &BranchDelta| b.target_id.is_some() && b.diff_stats.is_none();
+    if !repo_deltas.deltas.iter().any(missing) {
+        return repo_deltas.clone();
+    }
+    let mut repo_deltas = repo_deltas.clone();
+    add_diff_stats(&mut repo_deltas);
+    let mut repos_view: ViewRef<ReposView> = siv.find_id("mainView").unwrap();
+    repos_view.replace_repo_deltas(repo_deltas.clone());
+    repo_deltas
+}
+
+fn set_highlight(siv: &mut Cursive, pattern: Option<String>) {
+    let mut repos_view: ViewRef<ReposView> = siv.find_id("mainView").unwrap();
+    repos_view.set_highlight(pattern.clone());
+    let mut delta_view: ViewRef<DeltaView> = siv.find_id("deltaView").unwrap();
+    delta_view.set_highlight(pattern);
+}
+
+/// jumps to the next (or previous) line in the details containing the search
+/// pattern - continues with the next matching repo if there is none
+fn search(siv: &mut Cursive, backwards: bool, skip_selected_line: bool) {
+    let pattern = match siv.user_data::<Search>() {
+        Some(search) if !search.pattern.is_empty() => search.pattern.clone(),
+        _ => return,
+    };
+
+    let mut delta_view: ViewRef<DeltaView> = siv.find_id("deltaView").unwrap();
+    if delta_view.repo_deltas().is_some()
+        && delta_view.select_match(&pattern, backwards, !skip_selected_line)
+    {
+        return;
+    }
+    drop(delta_view);
+
+    let mut repos_view: ViewRef<ReposView> = siv.find_id("mainView").unwrap();
+    match repos_view.select_match(&pattern, backwards) {
+        Some((row, repo)) => {
+            drop(repos_view);
+            update(siv, row, &repo);
+            let mut delta_view: ViewRef<DeltaView> = siv.find_id("deltaView").unwrap();
+            delta_view.select_first_match(&pattern, backwards);
+        }
+        None => repos_view.show_message(&format!("Pattern not found: {}", pattern)),
+    }
+}
+
+/// restarts the search from the row selected when the search has been started
+fn search_incrementally(siv: &mut Cursive, pattern: &str) {
+    let origin_row = match siv.user_data::<Search>() {
+        Some(search) => search.origin_row,
+        None => return,
     };
+    siv.set_user_data(Search {
+        pattern: pattern.to_string(),
+        origin_row,
+    });
+
+    let mut repos_view: ViewRef<ReposView> = siv.find_id("mainView").unwrap();
+    let repo = repos_view.set_selected_row(origin_row);
+    drop(repos_view);
+    if let Some(repo) = repo {
+        update(siv, origin_row, &repo);
+    }
 
+    if pattern.is_empty() {
+        set_highlight(siv, None);
+    } else {
+        set_highlight(siv, Some(pattern.to_string()));
+        search(siv, false, false);
+    }
+}
+
+fn search_dialog() -> impl View {
+    let edit_view = EditView::new()
+        .on_edit(|s, pattern, _cursor| search_incrementally(s, pattern))
+        .on_submit(|s, _pattern| {
+            s.pop_layer();
+        })
+        .fixed_width(40);
+    OnEventView::new(Dialog::around(edit_view).title("Search")).on_event(Key::Esc, |s| {
+        s.pop_layer();
+        search_incrementally(s, "");
+    })
+}
+
+pub fn show(
+    model: Vec<RepoBranchDeltas>,
+    filter: Filter,
+    config: &Config,
+    total_nr_of_repos: usize,
+) {
     let mut siv = Cursive::default();
     let screen_size = siv.screen_size();
 
-    let mut repos_view = ReposView::from(model, total_nr_of_repos);
+    let mut repos_view = ReposView::from(model, filter, total_nr_of_repos);
+    let first_repo = repos_view.selected_repo_deltas();
 
     siv.load_toml(include_str!("../assets/style.toml")).unwrap();
 
     repos_view.update_status_bar(-1);
     repos_view.set_on_select(
-        move |siv: &mut Cursive, _row: usize, index: usize, status: &RepoBranchDeltas| {
-            let mut status_view: ViewRef<DeltaView> = siv.find_id("deltaView").unwrap();
-            status_view.set_repo_deltas(&status);
-            let mut repos_view: ViewRef<ReposView> = siv.find_id("mainView").unwrap();
-            repos_view.update_status_bar(index as i32);
+        move |siv: &mut Cursive, row: usize, _index: usize, status: &RepoBranchDeltas| {
+            update(siv, row, status)
         },
     );
     let landscape_format = screen_size.x / (screen_size.y * 3) >= 1;
@@ -73,6 +194,53 @@ pub fn show(model: Vec<RepoBranchDeltas>, config: &Config, total_nr_of_repos: us
             s.quit();
         }
     });
+    register_builtin_command('f', &mut siv, |s| {
+        if s.screen().len() > 1 {
+            return; //a dialog is already open
+        }
+        let repos_view: ViewRef<ReposView> = s.find_id("mainView").unwrap();
+        let dialog = filter_dialog(
+            repos_view.filter(),
+            &repos_view.branch_names(),
+            apply_filter,
+        );
+        drop(repos_view);
+        s.add_layer(dialog);
+    });
+    register_builtin_command('/', &mut siv, |s| {
+        if s.screen().len() > 1 {
+            return; //a dialog is already open
+        }
+        let mut repos_view: ViewRef<ReposView> = s.find_id("mainView").unwrap();
+        if let Some(origin_row) = repos_view.selected_row() {
+            drop(repos_view);
+            s.set_user_data(Search {
+                pattern: String::new(),
+                origin_row,
+            });
+            s.add_layer(search_dialog());
+        }
+    });
+    register_builtin_command('n', &mut siv, |s| search(s, false, true));
+    register_builtin_command('N', &mut siv, |s| search(s, true, true));
+    register_builtin_command('F', &mut siv, |s| {
+        if s.screen().len() > 1 {
+            return; //a dialog is already open
+        }
+        confirm_fast_forward(s);
+    });
+    register_builtin_command('M', &mut siv, |s| {
+        if s.screen().len() > 1 {
+            return; //a dialog is already open
+        }
+        run_trial_merge(s);
+    });
+    register_builtin_command('D', &mut siv, |s| {
+        if s.screen().len() > 1 {
+            return; //a dialog is already open
+        }
+        show_diff(s);
+    });
     register_builtin_command('k', &mut siv, |s| {
         let mut status_view: ViewRef<DeltaView> = s.find_id("deltaView").unwrap();
         status_view.on_event(Event::Key(Key::Up));
@@ -85,6 +253,20 @@ pub fn show(model: Vec<RepoBranchDeltas>, config: &Config, total_nr_of_repos: us
     if let Some(repo) = first_repo {
         update(&mut siv, 0, &repo)
     }
+    let shadowed_keys = config
+        .custom_command
+        .iter()
+        .map(|cmd| cmd.key)
+        .filter(|key| BUILTIN_KEYS.contains(key))
+        .map(|key| key.to_string())
+        .collect::<Vec<_>>();
+    if !shadowed_keys.is_empty() {
+        let mut repos_view: ViewRef<ReposView> = siv.find_id("mainView").unwrap();
+        repos_view.show_message(&format!(
+            "Custom commands ignored, their keys are taken by builtin commands: {}",
+            shadowed_keys.join(", ")
+        ));
+    }
     siv.run();
 }
 
@@ -92,6 +274,7 @@ fn register_builtin_command<F>(ch: char, siv: &mut Cursive, cb: F)
 where
     F: FnMut(&mut Cursive) + 'static,
 {
+    debug_assert!(BUILTIN_KEYS.contains(&ch));
     siv.clear_global_callbacks(ch); //to avoid that custom commands are taking over one of our builtin shortcuts
     siv.add_global_callback(ch, cb);
 }
@@ -99,18 +282,360 @@ where
 fn register_custom_commands(config: &Config, siv: &mut Cursive) {
     for cmd in &config.custom_command {
         let executable = cmd.executable.clone();
-        let args = cmd.args.clone();
+        let args = cmd.args.clone().unwrap_or_default();
+        let foreground = cmd.foreground;
+        let capture_output = cmd.capture_output;
+        let batch = cmd.batch;
 
         siv.add_global_callback(cmd.key, move |s| {
+            if batch {
+                confirm_batch(s, &executable, &args);
+                return;
+            }
+
             let delta_view: ViewRef<DeltaView> = s.find_id("deltaView").unwrap();
-            if let Some(status) = &delta_view.repo_deltas() {
-                let result =
-                    execute_on_repo(&executable, args.as_ref().unwrap_or(&String::new()), status);
-                if let Some(error) = &result.err() {
-                    let mut repos_view: ViewRef<ReposView> = s.find_id("mainView").unwrap();
-                    repos_view.show_error("Failed to open gitk", error);
-                }
+            let repo_deltas = match delta_view.repo_deltas() {
+                Some(repo_deltas) => repo_deltas.clone(),
+                None => return,
+            };
+            let branch = delta_view.selected_branch().cloned();
+            let commit = delta_view.selected_commit();
+            drop(delta_view);
+
+            let context = CommandContext {
+                repo_deltas: &repo_deltas,
+                branch: branch.as_ref(),
+                commit,
+            };
+            let result = if foreground {
+                suspended(s, || {
+                    execute_on_repo_in_foreground(&executable, &args, &context)
+                })
+                .map(|status| {
+                    if !status.success() {
+                        let mut repos_view: ViewRef<ReposView> = s.find_id("mainView").unwrap();
+                        repos_view.show_message(&format!("{} failed: {}", executable, status));
+                    }
+                })
+            } else if capture_output {
+                execute_on_repo_and_capture(&executable, &args, &context)
+                    .map(|output| s.add_layer(output_dialog(&executable, &output)))
+            } else {
+                execute_on_repo(&executable, &args, &context).map(|_child| ())
+            };
+            if let Some(error) = &result.err() {
+                let mut repos_view: ViewRef<ReposView> = s.find_id("mainView").unwrap();
+                repos_view.show_error(&format!("Failed to run {}", executable), error);
             }
         });
     }
 }
+
+/// asks for confirmation before executing a custom command on all listed repos
+fn confirm_batch(siv: &mut Cursive, executable: &str, args: &str) {
+    let mut repos_view: ViewRef<ReposView> = siv.find_id("mainView").unwrap();
+    let repos = repos_view.visible_repos();
+    drop(repos_view);
+    let delta_view: ViewRef<DeltaView> = siv.find_id("deltaView").unwrap();
+    let branch_index = delta_view.selected_branch_index();
+    drop(delta_view);
+    if repos.is_empty() {
+        return;
+    }
+
+    let command_line = format!("{} {}", executable, args);
+    let question = format!("Run `{}` on {} repos?", command_line.trim(), repos.len());
+    let executable = executable.to_string();
+    let args = args.to_string();
+    siv.add_layer(
+        Dialog::text(question)
+            .title("Confirm")
+            .button("Run", move |s| {
+                s.pop_layer();
+                run_batch(s, &executable, &args, repos.clone(), branch_index);
+            })
+            .dismiss_button("Cancel"),
+    );
+}
+
+/// asks for confirmation before fast-forwarding the selected target branch to HEAD
+fn confirm_fast_forward(siv: &mut Cursive) {
+    let delta_view: ViewRef<DeltaView> = siv.find_id("deltaView").unwrap();
+    let (repo_deltas, branch) = match (delta_view.repo_deltas(), delta_view.selected_branch()) {
+        (Some(repo_deltas), Some(branch)) => (repo_deltas.clone(), branch.clone()),
+        _ => return,
+    };
+    drop(delta_view);
+
+    //dry-run first, so that we don't ask for something which is going to fail anyway
+    if let Err(e) = fast_forward(&repo_deltas, &branch, true) {
+        let mut repos_view: ViewRef<ReposView> = siv.find_id("mainView").unwrap();
+        repos_view.show_message(&format!("Can't fast-forward {}: {}", branch.branch_name, e));
+        return;
+    }
+
+    let question = format!(
+        "Fast-forward {} of {} to {}?",
+        describe(&repo_deltas, &branch),
+        repo_deltas.repo.rel_path,
+        repo_deltas.base
+    );
+    siv.add_layer(
+        Dialog::text(question)
+            .title("Confirm")
+            .button("Fast-forward", move |s| {
+                s.pop_layer();
+                run_fast_forward(s, &repo_deltas, &branch);
+            })
+            .dismiss_button("Cancel"),
+    );
+}
+
+/// fast-forwards the given target branch and scans the repo again
+fn run_fast_forward(siv: &mut Cursive, repo_deltas: &RepoBranchDeltas, branch: &BranchDelta) {
+    let mut repos_view: ViewRef<ReposView> = siv.find_id("mainView").unwrap();
+    if let Err(e) = fast_forward(repo_deltas, branch, false) {
+        repos_view.show_message(&format!(
+            "Failed to fast-forward {}: {}",
+            branch.branch_name, e
+        ));
+        return;
+    }
+
+    let branch_names = repos_view.branch_names();
+    let branch_names = branch_names.iter().map(|b| b.as_str()).collect::<Vec<_>>();
+    let repo_deltas = rescan(repo_deltas, &branch_names);
+    repos_view.replace_repo_deltas(repo_deltas.clone());
+    let message = match repo_deltas
+        .deltas
+        .iter()
+        .find(|b| b.branch_name == branch.branch_name)
+    {
+        Some(new_branch) => format!(
+            "Fast-forwarded {}: now {}",
+            branch.branch_name, new_branch.delta
+        ),
+        None => format!("Fast-forwarded {}", branch.branch_name),
+    };
+    repos_view.show_message(&message);
+    drop(repos_view);
+
+    let mut delta_view: ViewRef<DeltaView> = siv.find_id("deltaView").unwrap();
+    delta_view.set_repo_deltas(&repo_deltas);
+}
+
+/// merges HEAD into the selected target branch in memory and shows the result in
+/// the details; offers to create the merge commit if the merge is clean
+fn run_trial_merge(siv: &mut Cursive) {
+    let delta_view: ViewRef<DeltaView> = siv.find_id("deltaView").unwrap();
+    let n = delta_view.selected_branch_index();
+    let mut repo_deltas = match delta_view.repo_deltas() {
+        Some(repo_deltas) if n < repo_deltas.deltas.len() => repo_deltas.clone(),
+        _ => return,
+    };
+    drop(delta_view);
+
+    let trial_merge = trial_merge_branch(&repo_deltas, &repo_deltas.deltas[n]);
+    repo_deltas.deltas[n].trial_merge = Some(trial_merge.clone());
+    let branch = repo_deltas.deltas[n].clone();
+
+    let mut repos_view: ViewRef<ReposView> = siv.find_id("mainView").unwrap();
+    repos_view.replace_repo_deltas(repo_deltas.clone());
+    repos_view.show_message(&match &trial_merge {
+        Ok(t) if t.conflicts.is_empty() => {
+            format!(
+                "Merging {} into {} is clean",
+                repo_deltas.base, branch.branch_name
+            )
+        }
+        Ok(t) => format!(
+            "Merging {} into {} would conflict in {} files",
+            repo_deltas.base,
+            branch.branch_name,
+            t.conflicts.len()
+        ),
+        Err(e) => format!("Trial merge into {} failed: {}", branch.branch_name, e),
+    });
+    drop(repos_view);
+    let mut delta_view: ViewRef<DeltaView> = siv.find_id("deltaView").unwrap();
+    delta_view.update_repo_deltas(&repo_deltas);
+    drop(delta_view);
+
+    let clean = trial_merge.is_ok_and(|t| t.conflicts.is_empty());
+    if clean && branch.delta == Delta::NotConsolidated {
+        let question = format!(
+            "Merging {} into {} of {} is clean. Create the merge commit on {}?",
+            repo_deltas.base, branch.branch_name, repo_deltas.repo.rel_path, branch.branch_name
+        );
+        siv.add_layer(
+            Dialog::text(question)
+                .title("Trial merge")
+                .button("Create merge commit", move |s| {
+                    s.pop_layer();
+                    run_merge(s, &repo_deltas, &branch);
+                })
+                .dismiss_button("Cancel"),
+        );
+    }
+}
+
+/// creates the merge commit on the given target branch and scans the repo again
+fn run_merge(siv: &mut Cursive, repo_deltas: &RepoBranchDeltas, branch: &BranchDelta) {
+    let mut repos_view: ViewRef<ReposView> = siv.find_id("mainView").unwrap();
+    let merge_id = match create_merge_commit(repo_deltas, branch) {
+        Ok(merge_id) => merge_id,
+        Err(e) => {
+            repos_view.show_message(&format!(
+                "Failed to merge {} into {}: {}",
+                repo_deltas.base, branch.branch_name, e
+            ));
+            return;
+        }
+    };
+
+    let branch_names = repos_view.branch_names();
+    let branch_names = branch_names.iter().map(|b| b.as_str()).collect::<Vec<_>>();
+    let repo_deltas = rescan(repo_deltas, &branch_names);
+    repos_view.replace_repo_deltas(repo_deltas.clone());
+    repos_view.show_message(&format!(
+        "Created merge commit {} on {}",
+        &merge_id.to_string()[..7],
+        branch.branch_name
+    ));
+    drop(repos_view);
+
+    let mut delta_view: ViewRef<DeltaView> = siv.find_id("deltaView").unwrap();
+    delta_view.update_repo_deltas(&repo_deltas);
+}
+
+/// lists the files changed between HEAD and the selected target branch,
+/// `Enter` shows the diff of the selected file
+fn show_diff(siv: &mut Cursive) {
+    let delta_view: ViewRef<DeltaView> = siv.find_id("deltaView").unwrap();
+    let (repo_deltas, branch) = match (delta_view.repo_deltas(), delta_view.selected_branch()) {
+        (Some(repo_deltas), Some(branch)) => (repo_deltas.clone(), branch.clone()),
+        _ => return,
+    };
+    drop(delta_view);
+
+    let title = format!(
+        "{}: {}..{}",
+        repo_deltas.repo.rel_path, repo_deltas.base, branch.branch_name
+    );
+    let files = changed_files(&repo_deltas, &branch);
+    let file_title = title.clone();
+    let file_list = DiffView::files(&files, move |s, path| {
+        let lines = file_diff(&repo_deltas, &branch, path);
+        s.add_layer(diff_layer(
+            format!("{} {}", file_title, path),
+            DiffView::file(&lines),
+        ));
+    });
+    siv.add_layer(diff_layer(title, file_list));
+}
+
+fn diff_layer(title: String, diff_view: DiffView) -> impl View {
+    OnEventView::new(
+        Dialog::around(diff_view)
+            .title(title)
+            .dismiss_button("Close")
+            .full_screen(),
+    )
+    .on_event(Key::Esc, |s| {
+        s.pop_layer();
+    })
+}
+
+/// executes a custom command on the given repos in the background and
+/// shows the results in a popup as soon as they are available
+fn run_batch(
+    siv: &mut Cursive,
+    executable: &str,
+    args: &str,
+    repos: Vec<RepoBranchDeltas>,
+    branch_index: usize,
+) {
+    let title = format!("{} {}", executable, args).trim().to_string();
+    let nr_of_repos = repos.len();
+    siv.add_layer(
+        Dialog::around(TextView::empty().with_id("batchResults").scrollable())
+            .title(format!("{} - 0 of {} done", title, nr_of_repos))
+            .dismiss_button("Close")
+            .with_id("batchDialog"),
+    );
+
+    let cb_sink = siv.cb_sink().clone();
+    let executable = executable.to_string();
+    let args = args.to_string();
+    thread::spawn(move || {
+        let done = AtomicUsize::new(0);
+        let results = batch::run(&executable, &args, &repos, Some(branch_index), |result| {
+            let done = done.fetch_add(1, Ordering::SeqCst) + 1;
+            let text = batch_result_to_text(result);
+            let title = format!("{} - {} of {} done", title, done, nr_of_repos);
+            cb_sink
+                .send(Box::new(move |s: &mut Cursive| {
+                    s.call_on_id("batchResults", |v: &mut TextView| v.append(text));
+                    s.call_on_id("batchDialog", |v: &mut Dialog| v.set_title(title));
+                }))
+                .ok();
+        });
+
+        let nr_of_failed = results.iter().filter(|r| !r.success()).count();
+        let summary = format!(
+            "{} - done on {} repos, failed on {}",
+            executable, nr_of_repos, nr_of_failed
+        );
+        cb_sink
+            .send(Box::new(move |s: &mut Cursive| {
+                s.call_on_id("batchDialog", |v: &mut Dialog| v.set_title(summary));
+            }))
+            .ok();
+    });
+}
+
+fn batch_result_to_text(result: &BatchResult) -> StyledString {
+    let mut text = StyledString::new();
+    match &result.output {
+        Ok(output) if output.status.success() => {
+            text.append_styled(format!("✓ {}\n", result.repo), *GREEN)
+        }
+        Ok(output) => text.append_styled(format!("✗ {} ({})\n", result.repo, output.status), *RED),
+        Err(e) => text.append_styled(format!("✗ {} ({})\n", result.repo, e), *RED),
+    }
+    if let Ok(output) = &result.output {
+        let mut output_text = String::from_utf8_lossy(&output.stdout).to_string();
+        output_text += &String::from_utf8_lossy(&output.stderr);
+        for line in output_text.lines() {
+            text.append_plain(format!("    {}\n", line));
+        }
+    }
+    text
+}
+
+/// shows stdout and (in red) stderr of a custom command
+fn output_dialog(executable: &str, output: &Output) -> impl View {
+    let mut text = StyledString::plain(String::from_utf8_lossy(&output.stdout));
+    text.append_styled(String::from_utf8_lossy(&output.stderr), *RED);
+    if text.is_empty() {
+        text.append_plain("(no output)");
+    }
+
+    Dialog::around(TextView::new(text).scrollable())
+        .title(format!("{} - {}", executable, output.status))
+        .dismiss_button("Close")
+}
+
+/// hands the terminal over to `f` and restores the UI afterwards
+fn suspended<F, R>(siv: &mut Cursive, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    ncurses::def_prog_mode();
+    ncurses::endwin();
+    let result = f();
+    ncurses::reset_prog_mode();
+    ncurses::refresh();
+    siv.clear();
+    result
+}
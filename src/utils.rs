@@ -1,10 +1,12 @@
-use crate::model::{Repo, RepoBranchDeltas};
+use crate::model::{BranchDelta, PinnedRevision, Repo, RepoBranchDeltas, PINNED_PREFIX};
+use git2::Oid;
+use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::io;
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output, Stdio};
 use std::sync::Arc;
 
 /// returns a path pointing to he project.list file in
@@ -15,10 +17,7 @@ pub fn find_project_file() -> Result<PathBuf, io::Error> {
     if project_file.is_file() {
         Ok(project_file)
     } else {
-        Err(io::Error::new(
-            io::ErrorKind::Other,
-            "no project.list in .repo found",
-        ))
+        Err(io::Error::other("no project.list in .repo found"))
     }
 }
 
@@ -36,36 +35,191 @@ pub fn find_repo_folder() -> Result<PathBuf, io::Error> {
 pub fn find_repo_base_folder() -> Result<PathBuf, io::Error> {
     let cwd = env::current_dir()?;
     for parent in cwd.ancestors() {
-        for entry in fs::read_dir(&parent)? {
+        for entry in fs::read_dir(parent)? {
             let entry = entry?;
             if entry.path().is_dir() && entry.file_name() == ".repo" {
                 return Ok(parent.to_path_buf());
             }
         }
     }
-    Err(io::Error::new(
-        io::ErrorKind::Other,
-        "no .repo folder found",
-    ))
+    Err(io::Error::other("no .repo folder found"))
 }
 
-/// executes an external executable with given arguments;
-/// if the pattern "{}" is found in the args parameter, it
-/// is replaced with the ID of the given commit
+/// the selection in the UI a custom command is executed on
+pub struct CommandContext<'a> {
+    pub repo_deltas: &'a RepoBranchDeltas,
+    pub branch: Option<&'a BranchDelta>,
+    pub commit: Option<Oid>,
+}
+
+/// executes an external executable with given arguments; the
+/// arguments are split into words like a shell does and placeholders
+/// (see `expand_placeholders()`) are replaced afterwards
 pub fn execute_on_repo(
     exec: &str,
     args: &str,
-    status: &RepoBranchDeltas,
+    context: &CommandContext,
 ) -> Result<std::process::Child, std::io::Error> {
-    Command::new(exec)
-        .current_dir(&status.repo.abs_path)
-        .args(args.split(' '))
+    command_on_repo(exec, args, context)?
         .stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .spawn()
 }
 
+/// like `execute_on_repo()` but the executable inherits stdin/stdout/stderr
+/// and the function returns after it has exited
+pub fn execute_on_repo_in_foreground(
+    exec: &str,
+    args: &str,
+    context: &CommandContext,
+) -> Result<ExitStatus, std::io::Error> {
+    command_on_repo(exec, args, context)?.status()
+}
+
+/// like `execute_on_repo()` but waits for the executable and returns its output
+pub fn execute_on_repo_and_capture(
+    exec: &str,
+    args: &str,
+    context: &CommandContext,
+) -> Result<Output, std::io::Error> {
+    command_on_repo(exec, args, context)?
+        .stdin(Stdio::null())
+        .output()
+}
+
+fn command_on_repo(
+    exec: &str,
+    args: &str,
+    context: &CommandContext,
+) -> Result<Command, std::io::Error> {
+    let args = split_shell_words(args)
+        .and_then(|words| {
+            words
+                .iter()
+                .map(|word| expand_placeholders(word, context))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    let mut command = Command::new(exec);
+    command
+        .current_dir(&context.repo_deltas.repo.abs_path)
+        .args(args);
+    Ok(command)
+}
+
+/// splits a command line into words: words are separated by whitespace,
+/// '...' and "..." quote whitespace and a backslash escapes the next
+/// character (within "..." only `"`, `\`, `$` and `` ` ``)
+pub fn split_shell_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some(word) = word.take() {
+                    words.push(word);
+                }
+            }
+            '\\' => {
+                let escaped = chars.next().ok_or("trailing backslash")?;
+                word.get_or_insert_with(String::new).push(escaped);
+            }
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next().ok_or("unterminated single quote")? {
+                        '\'' => break,
+                        c => word.push(c),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next().ok_or("unterminated double quote")? {
+                        '"' => break,
+                        '\\' => match chars.next().ok_or("unterminated double quote")? {
+                            c @ '"' | c @ '\\' | c @ '$' | c @ '`' => word.push(c),
+                            c => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                        },
+                        c => word.push(c),
+                    }
+                }
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+
+    Ok(words)
+}
+
+/// replaces the placeholders `{}` (selected commit, HEAD if none is selected),
+/// `{repo}`, `{abs_path}`, `{branch}`, `{head}`, `{target_sha}` and `{merge_base}`;
+/// unknown placeholders (e.g. in `HEAD@{u}`) are kept as they are. `{branch}` fails
+/// for pinned revisions as their target name is no branch (e.g. to push to).
+pub fn expand_placeholders(word: &str, context: &CommandContext) -> Result<String, String> {
+    let repo_deltas = context.repo_deltas;
+    let branch = || {
+        context
+            .branch
+            .ok_or_else(|| String::from("no target branch selected"))
+    };
+    let head = || {
+        repo_deltas
+            .head_id
+            .ok_or_else(|| format!("HEAD of {} unknown", repo_deltas.repo.rel_path))
+    };
+
+    let mut expanded = String::new();
+    let mut rest = word;
+    while let Some(start) = rest.find('{') {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        expanded.push_str(&rest[..start]);
+        match &rest[start + 1..end] {
+            "" => expanded.push_str(&context.commit.map_or_else(head, Ok)?.to_string()),
+            "repo" => expanded.push_str(&repo_deltas.repo.rel_path),
+            "abs_path" => expanded.push_str(&repo_deltas.repo.abs_path.to_string_lossy()),
+            "branch" => {
+                let branch = branch()?;
+                if branch.branch_name.starts_with(PINNED_PREFIX) {
+                    return Err(format!("{} is no branch", branch.branch_name));
+                }
+                expanded.push_str(&branch.branch_name);
+            }
+            "head" => expanded.push_str(&head()?.to_string()),
+            "target_sha" => {
+                let branch = branch()?;
+                let id = branch
+                    .target_id
+                    .ok_or_else(|| format!("branch {} not found", branch.branch_name))?;
+                expanded.push_str(&id.to_string());
+            }
+            "merge_base" => {
+                let branch = branch()?;
+                let id = branch.merge_base_id.ok_or_else(|| {
+                    format!("no merge-base of HEAD and {} found", branch.branch_name)
+                })?;
+                expanded.push_str(&id.to_string());
+            }
+            _ => expanded.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
 /// parses a flat list of local repo paths
 /// and creates a vector of Repos objects from it
 pub fn repos_from(
@@ -88,6 +242,33 @@ pub fn repos_from(
     Ok(repos)
 }
 
+/// pins the repos to the given revisions (by local repo path) under the given target name
+pub fn pin_repos(
+    repos: Vec<Arc<Repo>>,
+    target: &str,
+    pins: &HashMap<String, PinnedRevision>,
+) -> Vec<Arc<Repo>> {
+    repos
+        .into_iter()
+        .map(|repo| match pins.get(&repo.rel_path) {
+            Some(pinned) => Arc::new((*repo).clone().pinned_to(target, pinned.clone())),
+            None => repo,
+        })
+        .collect()
+}
+
+/// e.g. "manifest:release.xml" for "manifests/release.xml"
+pub fn pinned_target_name(manifest_file: &Path) -> String {
+    format!(
+        "{}{}",
+        PINNED_PREFIX,
+        manifest_file
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+    )
+}
+
 /// parses a flat list of local repo paths
 /// and creates a vector of Strings of it
 pub fn repos_paths_from(project_file: &std::fs::File) -> Result<Vec<String>, io::Error> {
@@ -100,3 +281,78 @@ pub fn repos_paths_from(project_file: &std::fs::File) -> Result<Vec<String>, io:
 
     Ok(repos)
 }
+
+#[cfg(test)]
+fn test_context_repo() -> RepoBranchDeltas {
+    use crate::model::Delta;
+
+    RepoBranchDeltas {
+        head_id: Some(Oid::from_str("1111111111111111111111111111111111111111").unwrap()),
+        ..RepoBranchDeltas::with_deltas(
+            "frameworks/base",
+            vec![BranchDelta {
+                target_id: Some(Oid::from_str("2222222222222222222222222222222222222222").unwrap()),
+                ..BranchDelta::with_delta("release", Delta::NotConsolidated)
+            }],
+        )
+    }
+}
+
+#[test]
+fn test_split_shell_words() {
+    assert_eq!(split_shell_words("").unwrap(), Vec::<String>::new());
+    assert_eq!(
+        split_shell_words("  -- tig  --all ").unwrap(),
+        ["--", "tig", "--all"]
+    );
+    assert_eq!(
+        split_shell_words(r#"-e 'git log' "a \"b\" \n" c\ d ''"#).unwrap(),
+        ["-e", "git log", r#"a "b" \n"#, "c d", ""]
+    );
+    assert!(split_shell_words("'unterminated").is_err());
+    assert!(split_shell_words("trailing\\").is_err());
+}
+
+#[test]
+fn test_expand_placeholders() {
+    let repo_deltas = test_context_repo();
+    let mut context = CommandContext {
+        repo_deltas: &repo_deltas,
+        branch: repo_deltas.deltas.first(),
+        commit: None,
+    };
+    let expand = |word: &str, context: &CommandContext| expand_placeholders(word, context);
+
+    assert_eq!(
+        expand("--select-commit={}", &context).unwrap(),
+        "--select-commit=1111111111111111111111111111111111111111"
+    );
+    assert_eq!(
+        expand("{repo}:{branch}..{target_sha}", &context).unwrap(),
+        "frameworks/base:release..2222222222222222222222222222222222222222"
+    );
+    assert_eq!(
+        expand("{abs_path}", &context).unwrap(),
+        "/ws/frameworks/base"
+    );
+    assert_eq!(expand("HEAD@{u}", &context).unwrap(), "HEAD@{u}");
+    assert_eq!(expand("{unterminated", &context).unwrap(), "{unterminated");
+    assert!(expand("{merge_base}", &context).is_err());
+
+    context.commit = Some(Oid::from_str("3333333333333333333333333333333333333333").unwrap());
+    assert_eq!(
+        expand("{}", &context).unwrap(),
+        "3333333333333333333333333333333333333333"
+    );
+    context.branch = None;
+    assert!(expand("{branch}", &context).is_err());
+
+    let mut pinned = repo_deltas.deltas[0].clone();
+    pinned.branch_name = format!("{}default.xml", PINNED_PREFIX);
+    context.branch = Some(&pinned);
+    assert!(expand("HEAD:refs/for/{branch}", &context).is_err());
+    assert_eq!(
+        expand("{target_sha}", &context).unwrap(),
+        "2222222222222222222222222222222222222222"
+    );
+}
@@ -1,12 +1,17 @@
-use crate::model::{Delta, RepoBranchDeltas};
+use crate::model::{BranchDelta, CommitInfo, Delta, DiffStats, RepoBranchDeltas, TrialMerge};
 use crate::styles::{BLUE, GREEN, RED, WHITE, YELLOW};
 use crate::views::ListView;
 use cursive::theme::ColorStyle;
 use cursive::view::ViewWrapper;
+use git2::Oid;
 
 pub struct DeltaView {
     list_view: ListView,
     repo_deltas: Option<RepoBranchDeltas>,
+    highlight: Option<String>,
+    /// first row of the lines belonging to a target branch (given by its index)
+    branch_rows: Vec<(usize, usize)>,
+    commit_rows: Vec<(usize, Oid)>,
 }
 
 impl DeltaView {
@@ -14,11 +19,98 @@ impl DeltaView {
         DeltaView {
             list_view: ListView::new(),
             repo_deltas: None,
+            highlight: None,
+            branch_rows: vec![],
+            commit_rows: vec![],
         }
     }
 
+    pub fn clear(&mut self) {
+        self.repo_deltas = None;
+        self.reset();
+    }
+
     fn reset(&mut self) {
         self.list_view = ListView::new();
+        self.list_view.set_highlight(self.highlight.clone());
+        self.branch_rows.clear();
+        self.commit_rows.clear();
+    }
+
+    fn start_branch_rows(&mut self, n: usize) {
+        self.branch_rows.push((self.list_view.len(), n));
+    }
+
+    /// the target branch the selected line belongs to, the
+    /// first one if the line doesn't belong to a branch
+    pub fn selected_branch(&self) -> Option<&BranchDelta> {
+        let deltas = &self.repo_deltas.as_ref()?.deltas;
+        deltas.get(self.selected_branch_index())
+    }
+
+    /// like `selected_branch()` but returns the index of the branch
+    pub fn selected_branch_index(&self) -> usize {
+        let row = self.list_view.row().unwrap_or(0);
+        self.branch_rows
+            .iter()
+            .rev()
+            .find(|(first_row, _)| *first_row <= row)
+            .map_or(0, |(_, n)| *n)
+    }
+
+    /// the commit shown in the selected line
+    pub fn selected_commit(&self) -> Option<Oid> {
+        let row = self.list_view.row()?;
+        self.commit_rows
+            .iter()
+            .find(|(commit_row, _)| *commit_row == row)
+            .map(|(_, id)| *id)
+    }
+
+    pub fn set_highlight(&mut self, pattern: Option<String>) {
+        self.list_view.set_highlight(pattern.clone());
+        self.highlight = pattern;
+    }
+
+    /// selects the next (or previous) line containing `pattern`, starting
+    /// with the selected line if `inclusive` is set; returns false if
+    /// there is no such line
+    pub fn select_match(&mut self, pattern: &str, backwards: bool, inclusive: bool) -> bool {
+        let from = self.list_view.row().unwrap_or(0);
+        match self.list_view.find(pattern, from, backwards, inclusive) {
+            Some(row) => {
+                self.list_view.set_selected_row(row);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// selects the first (or last if `backwards` is set) line containing `pattern`
+    pub fn select_first_match(&mut self, pattern: &str, backwards: bool) -> bool {
+        let from = if backwards {
+            self.list_view.len().saturating_sub(1)
+        } else {
+            0
+        };
+        self.list_view.set_selected_row(from);
+        self.select_match(pattern, backwards, true)
+    }
+
+    /// true if any line shown for the given repo contains `pattern`
+    pub fn contains(repo_deltas: &RepoBranchDeltas, pattern: &str) -> bool {
+        let mut view = DeltaView::empty();
+        view.set_repo_deltas(repo_deltas);
+        view.select_first_match(pattern, false)
+    }
+
+    /// like `set_repo_deltas()` but keeps the selected line, e.g. after the repo has been changed
+    pub fn update_repo_deltas(&mut self, repo_deltas: &RepoBranchDeltas) {
+        let row = self.list_view.row();
+        self.set_repo_deltas(repo_deltas);
+        if let Some(row) = row.filter(|row| *row < self.list_view.len()) {
+            self.list_view.set_selected_row(row);
+        }
     }
 
     fn append_string(&mut self, s: String) {
@@ -36,64 +128,152 @@ impl DeltaView {
         self.reset();
 
         self.append_colorful_string(format!("{:30} {}", "git repo", repo_deltas.repo.rel_path), *WHITE);
+        if let Some(pinned) = repo_deltas.repo.pinned.get(&repo_deltas.base) {
+            self.append_colorful_string(format!("{:30} {} ({})", "base", repo_deltas.base, pinned.revision), *WHITE);
+        }
         self.append_string(String::new());
 
+        if let Some(error) = &repo_deltas.error {
+            self.append_colorful_string(error.to_string(), *RED);
+            return;
+        }
+
 //Summary
         self.append_colorful_string(String::from("Summary:"), *WHITE);
         self.append_string(String::new());
         self.append_colorful_string(format!("{:30}   {}", "Target Branch", "Delta"), *WHITE);
         self.append_colorful_string("===============================|==================================".to_string(), *WHITE);
-        for branch_delta in repo_deltas.deltas.iter() {
-            self.append_colorful_string(format!("{:30}   {}", branch_delta.branch_name.to_string(), Self::delta_to_string(&branch_delta.delta)), Self::delta_to_color(&branch_delta.delta));
+        for (n, branch_delta) in repo_deltas.deltas.iter().enumerate() {
+            self.start_branch_rows(n);
+            self.append_colorful_string(format!("{:30}   {}", branch_delta.branch_name.to_string(), Self::delta_to_string(&branch_delta.delta, &repo_deltas.base)), Self::delta_to_color(&branch_delta.delta));
         }
         self.append_string(String::new());
 
 //Details
         self.append_colorful_string(String::from("Details:"), *WHITE);
         self.append_string(String::new());
-        for branch_delta in repo_deltas.deltas.iter() {
+        for (n, branch_delta) in repo_deltas.deltas.iter().enumerate() {
             if branch_delta.delta == Delta::BranchNotFound{
                 continue;
             }
 
-            self.append_colorful_string(format!("{}", branch_delta.branch_name.to_string()), *WHITE);
+            self.start_branch_rows(n);
+            self.append_colorful_string(branch_delta.branch_name.clone(), *WHITE);
             self.append_colorful_string(String::from("==============================="), *WHITE);
-            self.append_colorful_string(format!("{}", Self::delta_to_string(&branch_delta.delta)), Self::delta_to_color(&branch_delta.delta));
+            self.append_colorful_string(Self::delta_to_string(&branch_delta.delta, &repo_deltas.base), Self::delta_to_color(&branch_delta.delta));
+            if let Some(pinned) = repo_deltas.repo.pinned.get(&branch_delta.branch_name) {
+                self.append_string(format!("Pinned revision: {}", pinned.revision));
+            }
             self.append_string(String::from("Distance from merge-base:"));
-            self.append_string(format!("  HEAD: {}", match &branch_delta.distance_head_to_merge_base {
+            self.append_string(format!("  {}: {}", repo_deltas.base, match &branch_delta.distance_head_to_merge_base {
                 Ok(v) => {
-                    format!("{} commits", v.to_string())
+                    format!("{} commits", v)
                 },
                 Err(e) => e.clone()
             }));
-            self.append_string(format!("  {}: {}",  branch_delta.branch_name.to_string(), match &branch_delta.distance_target_to_merge_base {
+            self.append_string(format!("  {}: {}",  branch_delta.branch_name, match &branch_delta.distance_target_to_merge_base {
                 Ok(v) => {
-                    format!("{} commits", v.to_string())
+                    format!("{} commits", v)
                 },
                 Err(e) => e.clone()
             }));
+            self.append_string(String::from("Changes:"));
+            if let Some(diff_stats) = &branch_delta.diff_stats {
+                self.append_diff_stats(&format!("{}..", repo_deltas.base), &branch_delta.branch_name, &diff_stats.head_to_target);
+                self.append_diff_stats("merge-base..", &repo_deltas.base, &diff_stats.merge_base_to_head);
+                self.append_diff_stats("merge-base..", &branch_delta.branch_name, &diff_stats.merge_base_to_target);
+            }
+            if let Some(trial_merge) = &branch_delta.trial_merge {
+                self.append_trial_merge(&repo_deltas.base, trial_merge);
+            }
+            self.append_string(format!("Commits only in {}:", repo_deltas.base));
+            self.append_commits(&branch_delta.commits_head_to_merge_base);
+            self.append_string(format!("Commits only in {}:", branch_delta.branch_name));
+            self.append_commits(&branch_delta.commits_target_to_merge_base);
 
             self.append_string(String::new());
         }
 
     }
 
+    fn append_diff_stats(&mut self, from: &str, to: &str, diff_stats: &Result<DiffStats, String>) {
+        let range = format!("{}{}:", from, to);
+        match diff_stats {
+            Ok(diff_stats) => self.append_string(format!("  {:30} {}", range, diff_stats)),
+            Err(e) => self.append_colorful_string(format!("  {:30} {}", range, e), *RED),
+        }
+    }
+
+    fn append_trial_merge(&mut self, base: &str, trial_merge: &Result<TrialMerge, String>) {
+        match trial_merge {
+            Ok(trial_merge) if trial_merge.conflicts.is_empty() => {
+                self.append_colorful_string(format!("Trial merge of {}: clean", base), *GREEN)
+            }
+            Ok(trial_merge) => {
+                self.append_colorful_string(
+                    format!(
+                        "Trial merge of {}: conflicts in {} files",
+                        base,
+                        trial_merge.conflicts.len()
+                    ),
+                    *RED,
+                );
+                for path in &trial_merge.conflicts {
+                    self.append_string(format!("  {}", path));
+                }
+            }
+            Err(e) => self.append_colorful_string(format!("Trial merge of {}: {}", base, e), *RED),
+        }
+    }
+
+    fn append_commits(&mut self, commits: &Result<Vec<CommitInfo>, String>) {
+        match commits {
+            Ok(commits) if commits.is_empty() => self.append_string(String::from("  none")),
+            Ok(commits) => {
+                for commit in commits {
+                    //same notation as `git cherry`: '-' marks an already picked commit
+                    let (marker, color) = if commit.picked {
+                        ('-', *GREEN)
+                    } else {
+                        ('+', *WHITE)
+                    };
+                    let id = commit.id.to_string();
+                    self.commit_rows.push((self.list_view.len(), commit.id));
+                    self.append_colorful_string(
+                        format!(
+                            "  {} {} {} {:20.20} {}",
+                            marker,
+                            &id[..10],
+                            commit.time.format("%Y-%m-%d %H:%M"),
+                            commit.author,
+                            commit.summary
+                        ),
+                        color,
+                    );
+                }
+            }
+            Err(e) => self.append_colorful_string(format!("  {}", e), *RED),
+        }
+    }
+
     pub fn repo_deltas(&self) -> &Option<RepoBranchDeltas> {
         &self.repo_deltas
     }
 
-    fn delta_to_color(delta: &Delta) -> ColorStyle {
+    pub fn delta_to_color(delta: &Delta) -> ColorStyle {
         match delta {
             Delta::ConsolidatedBySameCommit => *GREEN,
             Delta::ConsolidatedByMergeCommit => *GREEN,
             Delta::ConsolidatedByEqualContent => *GREEN,
+            Delta::ConsolidatedByCherryPick => *GREEN,
             Delta::NotConsolidatedButFastForwardable => *YELLOW,
             Delta::NotConsolidated => *RED,
             Delta::BranchNotFound => *BLUE,
         }
     }
 
-    fn delta_to_string(delta: &Delta) -> String {
+    /// the description of the delta with "HEAD" replaced by the given base
+    fn delta_to_string(delta: &Delta, base: &str) -> String {
         match delta {
             Delta::ConsolidatedBySameCommit => {
                 "HEAD consolidated: points to the same commit as HEAD"
@@ -104,13 +284,16 @@ impl DeltaView {
             Delta::ConsolidatedByEqualContent => {
                 "HEAD consolidated: content same as HEAD (however history differs)"
             }
+            Delta::ConsolidatedByCherryPick => {
+                "HEAD consolidated: all commits of HEAD have been cherry-picked"
+            }
             Delta::NotConsolidatedButFastForwardable => {
                 "HEAD not consolidated: can be fast forwarded to HEAD"
             }
             Delta::NotConsolidated => "HEAD not consolidated: and not fast forwardable",
             Delta::BranchNotFound => "branch not found",
         }
-        .to_string()
+        .replace("HEAD", base)
     }
 }
 
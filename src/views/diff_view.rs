@@ -0,0 +1,81 @@
+use crate::diff::{ChangedFile, DiffLine};
+use crate::styles::{GREEN, LIGHT_BLUE, RED, WHITE};
+use crate::views::ListView;
+use cursive::view::ViewWrapper;
+use cursive::Cursive;
+
+/// shows the files changed between HEAD and a target branch or
+/// the unified diff of a single file
+pub struct DiffView {
+    list_view: ListView,
+}
+
+impl DiffView {
+    /// lists the changed files, `on_submit` is called with the path of the selected one
+    pub fn files<F>(files: &Result<Vec<ChangedFile>, String>, on_submit: F) -> Self
+    where
+        F: Fn(&mut Cursive, &str) + 'static,
+    {
+        let mut list_view = ListView::new();
+        match files {
+            Ok(files) if files.is_empty() => list_view.insert_string(String::from("no changes")),
+            Ok(files) => {
+                for file in files {
+                    list_view.insert_string(format!(
+                        "{} {:>5} {:>5}  {}",
+                        file.status,
+                        format!("+{}", file.insertions),
+                        format!("-{}", file.deletions),
+                        file.path
+                    ));
+                }
+                let paths = files.iter().map(|f| f.path.clone()).collect::<Vec<_>>();
+                list_view.set_on_submit(move |siv, _row, index| on_submit(siv, &paths[index]));
+            }
+            Err(e) => list_view.insert_colorful_string(e.clone(), *RED),
+        }
+        DiffView { list_view }
+    }
+
+    /// colors the lines like `git diff --color` does
+    pub fn file(lines: &Result<Vec<DiffLine>, String>) -> Self {
+        let mut list_view = ListView::new();
+        match lines {
+            Ok(lines) if lines.is_empty() => list_view.insert_string(String::from("no changes")),
+            Ok(lines) => {
+                for line in lines {
+                    //tabs would break the layout
+                    let content = line.content.replace('\t', "    ");
+                    match line.origin {
+                        'F' => list_view.insert_colorful_string(content, *WHITE),
+                        'H' => list_view.insert_colorful_string(content, *LIGHT_BLUE),
+                        '+' => list_view.insert_colorful_string(format!("+{}", content), *GREEN),
+                        '-' => list_view.insert_colorful_string(format!("-{}", content), *RED),
+                        ' ' => list_view.insert_string(format!(" {}", content)),
+                        _ => list_view.insert_string(content),
+                    }
+                }
+            }
+            Err(e) => list_view.insert_colorful_string(e.clone(), *RED),
+        }
+        DiffView { list_view }
+    }
+}
+
+impl ViewWrapper for DiffView {
+    type V = ListView;
+
+    fn with_view<F, R>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&Self::V) -> R,
+    {
+        Some(f(&self.list_view))
+    }
+
+    fn with_view_mut<F, R>(&mut self, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut Self::V) -> R,
+    {
+        Some(f(&mut self.list_view))
+    }
+}
@@ -0,0 +1,122 @@
+use crate::model::{Delta, Filter};
+use crate::views::RepoFilter;
+use cursive::traits::*;
+use cursive::views::{Checkbox, Dialog, EditView, LinearLayout, RadioGroup, TextView};
+use cursive::Cursive;
+
+const REPO_ERRORS_ID: &str = "filter.RepoErrors";
+const PATH_PATTERN_ID: &str = "filter.PathPattern";
+
+/// a dialog to change the filter of the list of repositories; `on_apply`
+/// is called with the new filter when the dialog is confirmed
+pub fn filter_dialog<F>(filter: &RepoFilter, branch_names: &[String], on_apply: F) -> Dialog
+where
+    F: Fn(&mut Cursive, RepoFilter) + 'static,
+{
+    let mut states = LinearLayout::vertical();
+    for delta in Delta::ALL.iter() {
+        states.add_child(checkbox(
+            &checkbox_id(delta),
+            &delta.to_string(),
+            filter.filter.includes_delta(delta),
+        ));
+    }
+    states.add_child(checkbox(
+        REPO_ERRORS_ID,
+        "repos which couldn't be scanned",
+        filter.filter.include_repo_errors,
+    ));
+
+    let mut branch_group: RadioGroup<Option<usize>> = RadioGroup::new();
+    let mut branches = LinearLayout::vertical();
+    let mut add_branch = |value: Option<usize>, label: &str| {
+        let button = branch_group.button(value, label);
+        branches.add_child(if filter.branch == value {
+            button.selected()
+        } else {
+            button
+        });
+    };
+    add_branch(None, "any branch");
+    for (n, name) in branch_names.iter().enumerate() {
+        add_branch(Some(n), name);
+    }
+
+    let path_pattern = EditView::new()
+        .content(filter.path_pattern.clone())
+        .with_id(PATH_PATTERN_ID)
+        .fixed_width(40);
+
+    let content = LinearLayout::vertical()
+        .child(TextView::new("Show repos where the delta is one of:"))
+        .child(states)
+        .child(TextView::new(" "))
+        .child(TextView::new("... on:"))
+        .child(branches)
+        .child(TextView::new(" "))
+        .child(TextView::new(
+            "... and the path matches (substring or regex):",
+        ))
+        .child(path_pattern);
+
+    let repo_ignore_list = filter.filter.repo_ignore_list.clone();
+    Dialog::around(content)
+        .title("Filter")
+        .button("Apply", move |s| {
+            let mut filter = Filter {
+                include_consolidated_by_same_commit: false,
+                include_consolidated_by_merge_commit: false,
+                include_consolidated_by_equal_content: false,
+                include_consolidated_by_cherry_pick: false,
+                include_non_consolidated: false,
+                include_non_consolidated_but_ff_able: false,
+                include_branch_not_found: false,
+                include_repo_errors: is_checked(s, REPO_ERRORS_ID),
+                repo_ignore_list: repo_ignore_list.clone(),
+            };
+            for delta in Delta::ALL.iter() {
+                filter.set_includes_delta(delta, is_checked(s, &checkbox_id(delta)));
+            }
+            let path_pattern = s
+                .call_on_id(PATH_PATTERN_ID, |v: &mut EditView| v.get_content())
+                .unwrap();
+
+            s.pop_layer();
+            on_apply(
+                s,
+                RepoFilter {
+                    filter,
+                    branch: *branch_group.selection(),
+                    path_pattern: path_pattern.to_string(),
+                },
+            );
+        })
+        .button("Show all", |s| {
+            for delta in Delta::ALL.iter() {
+                set_checked(s, &checkbox_id(delta));
+            }
+            set_checked(s, REPO_ERRORS_ID);
+            s.call_on_id(PATH_PATTERN_ID, |v: &mut EditView| v.set_content(""));
+        })
+        .dismiss_button("Cancel")
+}
+
+fn checkbox(id: &str, label: &str, checked: bool) -> LinearLayout {
+    let mut checkbox = Checkbox::new();
+    checkbox.set_checked(checked);
+    LinearLayout::horizontal()
+        .child(checkbox.with_id(id))
+        .child(TextView::new(format!(" {}", label)))
+}
+
+fn checkbox_id(delta: &Delta) -> String {
+    format!("filter.{}", delta)
+}
+
+fn is_checked(s: &mut Cursive, id: &str) -> bool {
+    s.call_on_id(id, |c: &mut Checkbox| c.is_checked()).unwrap()
+}
+
+fn set_checked(s: &mut Cursive, id: &str) {
+    s.call_on_id(id, |c: &mut Checkbox| c.check());
+}
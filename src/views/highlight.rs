@@ -0,0 +1,52 @@
+use crate::styles::SEARCH_MATCH;
+use cursive::Printer;
+
+/// returns the char offsets of all (case-insensitive) occurrences of `pattern` in `text`
+pub fn find(text: &str, pattern: &str) -> Vec<usize> {
+    let text = text
+        .chars()
+        .map(|c| c.to_ascii_lowercase())
+        .collect::<Vec<_>>();
+    let pattern = pattern
+        .chars()
+        .map(|c| c.to_ascii_lowercase())
+        .collect::<Vec<_>>();
+    if pattern.is_empty() || pattern.len() > text.len() {
+        return vec![];
+    }
+
+    text.windows(pattern.len())
+        .enumerate()
+        .filter(|(_, window)| *window == pattern.as_slice())
+        .map(|(offset, _)| offset)
+        .collect()
+}
+
+pub fn contains(text: &str, pattern: &str) -> bool {
+    !find(text, pattern).is_empty()
+}
+
+/// draws all occurrences of `pattern` in `text` (as already printed at (0, 0)) again, highlighted
+pub fn draw_matches(printer: &Printer, text: &str, pattern: &str) {
+    let pattern_len = pattern.chars().count();
+    printer.with_color(*SEARCH_MATCH, |printer| {
+        for offset in find(text, pattern) {
+            let matched = text
+                .chars()
+                .skip(offset)
+                .take(pattern_len)
+                .collect::<String>();
+            printer.print((offset, 0), &matched);
+        }
+    });
+}
+
+#[test]
+fn test_find() {
+    assert_eq!(find("frameworks/base", "base"), [11]);
+    assert_eq!(find("Frameworks/Base", "fRAME"), [0]);
+    assert_eq!(find("aaa", "aa"), [0, 1]);
+    assert_eq!(find("↑2 base", "base"), [3]);
+    assert!(find("base", "").is_empty());
+    assert!(find("ba", "base").is_empty());
+}
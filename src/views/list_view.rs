@@ -6,6 +6,7 @@
 //  - Removed Header and grid
 //  - Removed columns and sorting
 //  - Allowing different colors items
+//  - Search with highlighted matches
 
 //! A basic list view implementation for [cursive](https://crates.io/crates/cursive).
 #![deny(
@@ -22,13 +23,13 @@
 extern crate cursive;
 
 // STD Dependencies -----------------------------------------------------------
+use crate::views::highlight;
 use std::cmp;
 use std::rc::Rc;
 
 // External Dependencies ------------------------------------------------------
 use cursive::direction::Direction;
 use cursive::event::{Callback, Event, EventResult, Key};
-use cursive::theme;
 use cursive::theme::{ColorStyle, Style};
 use cursive::utils::span::{SpannedStr, SpannedString};
 use cursive::vec::Vec2;
@@ -50,6 +51,7 @@ pub struct ListView {
     focus: usize,
     items: Vec<SpannedString<Style>>,
     rows_to_items: Vec<usize>,
+    highlight: Option<String>,
 
     // TODO Pass drawing offsets into the handlers so a popup menu
     // can be created easily?
@@ -80,6 +82,7 @@ impl ListView {
             focus: 0,
             items: Vec::new(),
             rows_to_items: Vec::new(),
+            highlight: None,
 
             on_submit: None,
             on_select: None,
@@ -190,6 +193,37 @@ impl ListView {
         self.with(|t| t.set_on_select(cb))
     }
 
+    /// Highlights all occurrences of the given pattern.
+    pub fn set_highlight(&mut self, pattern: Option<String>) {
+        self.highlight = pattern;
+    }
+
+    /// Returns the first row containing `pattern` after (or before if
+    /// `backwards` is set) the row `from`; `from` itself is included if
+    /// `inclusive` is set.
+    pub fn find(
+        &self,
+        pattern: &str,
+        from: usize,
+        backwards: bool,
+        inclusive: bool,
+    ) -> Option<usize> {
+        let matches = |row: &usize| {
+            self.items
+                .get(self.rows_to_items[*row])
+                .is_some_and(|item| highlight::contains(item.source(), pattern))
+        };
+        let skip = if inclusive { 0 } else { 1 };
+        if backwards {
+            (0..cmp::min(from + 1, self.rows_to_items.len()))
+                .rev()
+                .skip(skip)
+                .find(matches)
+        } else {
+            (from..self.rows_to_items.len()).skip(skip).find(matches)
+        }
+    }
+
     /// Removes all items from this view.
     pub fn clear(&mut self) {
         self.items.clear();
@@ -357,12 +391,15 @@ impl ListView {
         let item = &self.items[i];
         if focused {
             let item_without_color = SpannedString::<Style>::plain(item.source());
-            printer.with_style(theme::ColorStyle::highlight(), |printer: &Printer| {
+            printer.with_style(ColorStyle::highlight(), |printer: &Printer| {
                 printer.print_styled((0, 0), SpannedStr::from(&item_without_color));
             });
         } else {
             printer.print_styled((0, 0), SpannedStr::from(item));
         }
+        if let Some(pattern) = &self.highlight {
+            highlight::draw_matches(printer, item.source(), pattern);
+        }
     }
 
     fn focus_up(&mut self, n: usize) {
@@ -1,10 +1,15 @@
 mod delta_view;
+mod diff_view;
+mod filter_dialog;
+mod highlight;
 mod list_view;
 mod repos_view;
 mod seperator_view;
 mod table_view;
 
 pub use self::delta_view::DeltaView;
+pub use self::diff_view::DiffView;
+pub use self::filter_dialog::filter_dialog;
 pub use self::list_view::ListView;
-pub use self::repos_view::ReposView;
+pub use self::repos_view::{RepoFilter, ReposView};
 pub use self::seperator_view::SeperatorView;
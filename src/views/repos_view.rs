@@ -1,46 +1,171 @@
-use crate::model::RepoBranchDeltas;
-use crate::styles::WHITE;
+use crate::model::{branch_names, BranchDelta, Delta, Filter, RepoBranchDeltas};
+use crate::styles::{RED, WHITE};
 use crate::views::table_view::{TableView, TableViewItem};
+use crate::views::DeltaView;
 use cursive::theme::{BaseColor, Color, ColorStyle};
 use cursive::traits::*;
 use cursive::view::ViewWrapper;
 use cursive::views::{Canvas, LinearLayout, ViewRef};
 use cursive::Cursive;
+use regex::Regex;
 use std::cell::RefCell;
-use std::cmp::Ordering;
+use std::cmp::{self, Ordering};
 use std::rc::Rc;
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
 enum Column {
     Repo,
+    /// index of the target branch
+    Branch(usize),
 }
 
 impl TableViewItem<Column> for RepoBranchDeltas {
     fn to_column(&self, column: Column) -> String {
         match column {
             Column::Repo => self.repo.rel_path.clone(),
+            Column::Branch(n) => match (&self.error, self.deltas.get(n)) {
+                (Some(_), _) => String::from("! error"),
+                (None, Some(branch_delta)) => Self::branch_delta_to_column(branch_delta),
+                (None, None) => String::new(),
+            },
         }
     }
 
-    fn cmp(&self, _other: &Self, _column: Column) -> Ordering
+    fn cmp(&self, other: &Self, column: Column) -> Ordering
     where
         Self: Sized,
     {
-        Ordering::Equal
+        match column {
+            Column::Repo => self.repo.rel_path.cmp(&other.repo.rel_path),
+            Column::Branch(n) => {
+                Self::branch_sort_key(self, n).cmp(&Self::branch_sort_key(other, n))
+            }
+        }
+    }
+
+    fn to_column_color(&self, column: Column) -> Option<ColorStyle> {
+        match column {
+            Column::Repo => None,
+            Column::Branch(_) if self.error.is_some() => Some(*RED),
+            Column::Branch(n) => self
+                .deltas
+                .get(n)
+                .map(|branch_delta| DeltaView::delta_to_color(&branch_delta.delta)),
+        }
+    }
+}
+
+impl RepoBranchDeltas {
+    /// e.g. "x ↑2 ↓5 ✗3": the delta as a symbol, the number of commits of HEAD
+    /// missing on the branch, the number of commits only on the branch and
+    /// the number of conflicting files (if a trial merge has been performed)
+    fn branch_delta_to_column(branch_delta: &BranchDelta) -> String {
+        let symbol = match branch_delta.delta {
+            Delta::ConsolidatedBySameCommit => '=',
+            Delta::ConsolidatedByMergeCommit => 'm',
+            Delta::ConsolidatedByEqualContent => 'c',
+            Delta::ConsolidatedByCherryPick => 'p',
+            Delta::NotConsolidatedButFastForwardable => 'f',
+            Delta::NotConsolidated => 'x',
+            Delta::BranchNotFound => return String::from("? not found"),
+        };
+        let distance = |d: &Result<u32, String>| match d {
+            Ok(v) => v.to_string(),
+            Err(_) => String::from("?"),
+        };
+        let trial_merge = match &branch_delta.trial_merge {
+            Some(Ok(t)) if t.conflicts.is_empty() => String::from(" ✓"),
+            Some(Ok(t)) => format!(" ✗{}", t.conflicts.len()),
+            Some(Err(_)) => String::from(" ✗?"),
+            None => String::new(),
+        };
+        format!(
+            "{} ↑{} ↓{}{}",
+            symbol,
+            distance(&branch_delta.distance_head_to_merge_base),
+            distance(&branch_delta.distance_target_to_merge_base),
+            trial_merge
+        )
+    }
+
+    /// sorts by commits only on the branch first, then by commits of HEAD
+    /// missing on the branch and finally by the severity of the delta
+    fn branch_sort_key(&self, n: usize) -> (u32, u32, usize) {
+        match self.deltas.get(n) {
+            Some(branch_delta) if self.error.is_none() => (
+                *branch_delta
+                    .distance_target_to_merge_base
+                    .as_ref()
+                    .unwrap_or(&0),
+                *branch_delta
+                    .distance_head_to_merge_base
+                    .as_ref()
+                    .unwrap_or(&0),
+                Delta::ALL
+                    .iter()
+                    .position(|d| d == &branch_delta.delta)
+                    .unwrap_or(0),
+            ),
+            _ => (0, 0, Delta::ALL.len()),
+        }
+    }
+}
+
+/// the filter which can be changed interactively inside the TUI
+#[derive(Clone)]
+pub struct RepoFilter {
+    pub filter: Filter,
+    /// index of the target branch `filter` is applied to, any branch if None
+    pub branch: Option<usize>,
+    /// regex (or plain substring if it is not a valid regex) to match the repo path
+    pub path_pattern: String,
+}
+
+impl RepoFilter {
+    pub fn apply(&self, model: &[RepoBranchDeltas]) -> Vec<RepoBranchDeltas> {
+        let regex = Regex::new(&self.path_pattern).ok();
+        let path_matches = |path: &str| match &regex {
+            Some(regex) => regex.is_match(path),
+            None => path.contains(&self.path_pattern),
+        };
+
+        model
+            .iter()
+            .filter(|r| path_matches(&r.repo.rel_path))
+            .filter(|r| match self.branch {
+                Some(n) => self.filter.includes_branch(r, n),
+                None => self.filter.includes(r),
+            })
+            .cloned()
+            .collect()
     }
 }
 
 pub struct ReposView {
     layout: LinearLayout,
     status_bar_model: Rc<RefCell<String>>,
+    model: Vec<RepoBranchDeltas>,
+    filter: RepoFilter,
     number_of_filtered_repos: usize,
     number_of_total_repos: usize,
+    number_of_failed_repos: usize,
 }
 
 impl ReposView {
-    pub fn from(model: Vec<RepoBranchDeltas>, number_of_total_repos: usize) -> Self {
-        let number_of_filtered_repos = model.len();
-        let table = Self::new_table(model);
+    pub fn from(
+        model: Vec<RepoBranchDeltas>,
+        filter: Filter,
+        number_of_total_repos: usize,
+    ) -> Self {
+        let filter = RepoFilter {
+            filter,
+            branch: None,
+            path_pattern: String::new(),
+        };
+        let filtered_model = filter.apply(&model);
+        let number_of_filtered_repos = filtered_model.len();
+        let number_of_failed_repos = model.iter().filter(|r| r.error.is_some()).count();
+        let table = Self::new_table(&model, filtered_model);
         let status_bar_model = Rc::new(RefCell::new(String::from("")));
         let status_bar = Self::new_status_bar(status_bar_model.clone());
 
@@ -49,11 +174,111 @@ impl ReposView {
                 .child(table.with_id("table").full_screen())
                 .child(status_bar),
             status_bar_model,
+            model,
+            filter,
             number_of_filtered_repos,
             number_of_total_repos,
+            number_of_failed_repos,
+        }
+    }
+
+    pub fn filter(&self) -> &RepoFilter {
+        &self.filter
+    }
+
+    /// shows only the repos matching the given filter (without rescanning them)
+    pub fn set_filter(&mut self, filter: RepoFilter) {
+        let filtered_model = filter.apply(&self.model);
+        self.number_of_filtered_repos = filtered_model.len();
+        self.filter = filter;
+
+        let mut table: ViewRef<TableView<RepoBranchDeltas, Column>> =
+            self.layout.find_id("table").unwrap();
+        table.set_items(filtered_model);
+        table.sort();
+        table.set_selected_row(0);
+        let row = if table.is_empty() { -1 } else { 0 };
+        drop(table);
+
+        self.update_status_bar(row);
+    }
+
+    /// replaces a repo (e.g. after it has been scanned again) without applying
+    /// the filter again, so that the row stays visible and selected
+    pub fn replace_repo_deltas(&mut self, repo_deltas: RepoBranchDeltas) {
+        let same_repo = |r: &RepoBranchDeltas| r.repo.rel_path == repo_deltas.repo.rel_path;
+        if let Some(r) = self.model.iter_mut().find(|r| same_repo(r)) {
+            *r = repo_deltas.clone();
+        }
+        self.number_of_failed_repos = self.model.iter().filter(|r| r.error.is_some()).count();
+
+        let mut table: ViewRef<TableView<RepoBranchDeltas, Column>> =
+            self.layout.find_id("table").unwrap();
+        if let Some(r) = table.borrow_items_mut().iter_mut().find(|r| same_repo(r)) {
+            *r = repo_deltas;
         }
     }
 
+    pub fn set_highlight(&mut self, pattern: Option<String>) {
+        let mut table: ViewRef<TableView<RepoBranchDeltas, Column>> =
+            self.layout.find_id("table").unwrap();
+        table.set_highlight(pattern);
+    }
+
+    /// selects the next (or previous) repo with details containing `pattern`,
+    /// wraps around and ends with the selected repo itself
+    pub fn select_match(
+        &mut self,
+        pattern: &str,
+        backwards: bool,
+    ) -> Option<(usize, RepoBranchDeltas)> {
+        let mut table: ViewRef<TableView<RepoBranchDeltas, Column>> =
+            self.layout.find_id("table").unwrap();
+        let len = table.len();
+        let current = table.row()?;
+        let row = (1..=len)
+            .map(|n| {
+                if backwards {
+                    (current + len - n) % len
+                } else {
+                    (current + n) % len
+                }
+            })
+            .find(|row| {
+                table
+                    .borrow_item_at_row(*row)
+                    .is_some_and(|r| DeltaView::contains(r, pattern))
+            })?;
+        table.set_selected_row(row);
+        table.borrow_item_at_row(row).map(|r| (row, r.clone()))
+    }
+
+    /// all repos passing the filter, in the order as shown
+    pub fn visible_repos(&mut self) -> Vec<RepoBranchDeltas> {
+        let table: ViewRef<TableView<RepoBranchDeltas, Column>> =
+            self.layout.find_id("table").unwrap();
+        (0..table.len())
+            .filter_map(|row| table.borrow_item_at_row(row).cloned())
+            .collect()
+    }
+
+    pub fn selected_row(&mut self) -> Option<usize> {
+        let table: ViewRef<TableView<RepoBranchDeltas, Column>> =
+            self.layout.find_id("table").unwrap();
+        table.row()
+    }
+
+    pub fn set_selected_row(&mut self, row: usize) -> Option<RepoBranchDeltas> {
+        let mut table: ViewRef<TableView<RepoBranchDeltas, Column>> =
+            self.layout.find_id("table").unwrap();
+        table.set_selected_row(row);
+        table.borrow_item_at_row(row).cloned()
+    }
+
+    pub fn branch_names(&self) -> Vec<String> {
+        branch_names(&self.model)
+    }
+
     pub fn set_on_select<F>(&mut self, cb: F)
     where
         F: Fn(&mut Cursive, usize, usize, &RepoBranchDeltas) + 'static,
@@ -73,11 +298,29 @@ impl ReposView {
         });
     }
 
-    fn new_table(model: Vec<RepoBranchDeltas>) -> TableView<RepoBranchDeltas, Column> {
+    /// returns the repo in the currently selected row
+    pub fn selected_repo_deltas(&mut self) -> Option<RepoBranchDeltas> {
+        let mut table: ViewRef<TableView<RepoBranchDeltas, Column>> =
+            self.layout.find_id("table").unwrap();
+        let index = table.item()?;
+        table.borrow_item(index).cloned()
+    }
+
+    fn new_table(
+        model: &[RepoBranchDeltas],
+        filtered_model: Vec<RepoBranchDeltas>,
+    ) -> TableView<RepoBranchDeltas, Column> {
         let mut table =
             TableView::<RepoBranchDeltas, Column>::new()
                 .column(Column::Repo, "Repo", |c| c.color(*WHITE));
-        table.set_items(model);
+        for (n, branch) in branch_names(model).iter().enumerate() {
+            table = table.column(Column::Branch(n), branch.as_str(), |c| {
+                c.width(cmp::max(branch.chars().count() + 4, 16))
+                    .ordering(Ordering::Greater)
+            });
+        }
+        table.set_items(filtered_model);
+        table.sort_by(Column::Repo, Ordering::Less);
         table.set_selected_row(0);
 
         table
@@ -100,12 +343,20 @@ impl ReposView {
     }
 
     pub fn update_status_bar(&mut self, index: i32) {
-        (*self.status_bar_model).replace(format!(
+        let mut status = format!(
             "Repo {} of {} (unfiltered: {})",
             index + 1,
             self.number_of_filtered_repos,
             self.number_of_total_repos
-        ));
+        );
+        if self.number_of_failed_repos > 0 {
+            status += &format!(" - failed to scan: {}", self.number_of_failed_repos);
+        }
+        (*self.status_bar_model).replace(status);
+    }
+
+    pub fn show_message(&mut self, message: &str) {
+        (*self.status_bar_model).replace(message.to_string());
     }
 
     pub fn show_error(&mut self, context: &str, error: &std::io::Error) {
@@ -130,3 +381,77 @@ impl ViewWrapper for ReposView {
         Some(f(&mut self.layout))
     }
 }
+
+#[cfg(test)]
+fn test_model(deltas: &[(&str, Delta, Delta)]) -> Vec<RepoBranchDeltas> {
+    deltas
+        .iter()
+        .map(|(path, release, develop)| {
+            RepoBranchDeltas::with_deltas(
+                path,
+                vec![
+                    BranchDelta::with_delta("release", release.clone()),
+                    BranchDelta::with_delta("develop", develop.clone()),
+                ],
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_repo_filter() {
+    let model = test_model(&[
+        (
+            "frameworks/base",
+            Delta::NotConsolidated,
+            Delta::ConsolidatedBySameCommit,
+        ),
+        (
+            "frameworks/av",
+            Delta::ConsolidatedBySameCommit,
+            Delta::NotConsolidated,
+        ),
+        (
+            "device/x",
+            Delta::ConsolidatedBySameCommit,
+            Delta::ConsolidatedBySameCommit,
+        ),
+    ]);
+    let paths = |filter: &RepoFilter| {
+        filter
+            .apply(&model)
+            .iter()
+            .map(|r| r.repo.rel_path.clone())
+            .collect::<Vec<_>>()
+    };
+    let mut filter = RepoFilter {
+        filter: Filter {
+            include_consolidated_by_same_commit: false,
+            include_consolidated_by_merge_commit: true,
+            include_consolidated_by_equal_content: true,
+            include_consolidated_by_cherry_pick: true,
+            include_non_consolidated: true,
+            include_non_consolidated_but_ff_able: true,
+            include_branch_not_found: true,
+            include_repo_errors: true,
+            repo_ignore_list: None,
+        },
+        branch: None,
+        path_pattern: String::new(),
+    };
+    assert_eq!(paths(&filter), ["frameworks/base", "frameworks/av"]);
+
+    filter.branch = Some(1);
+    assert_eq!(paths(&filter), ["frameworks/av"]);
+
+    filter.branch = None;
+    filter.path_pattern = String::from("base$");
+    assert_eq!(paths(&filter), ["frameworks/base"]);
+
+    //not a valid regex, used as substring
+    filter.filter.include_consolidated_by_same_commit = true;
+    filter.path_pattern = String::from("/x(");
+    assert!(paths(&filter).is_empty());
+    filter.path_pattern = String::from("ice/");
+    assert_eq!(paths(&filter), ["device/x"]);
+}
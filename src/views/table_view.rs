@@ -3,8 +3,9 @@
 // - MIT license (LICENSE-MIT or http://opensource.org/licenses/MIT) at your option.
 
 // Changes:
-//  - Removed Header and grid
+//  - Removed grid, header reduced to a single line
 //  - Allowing different colors for cells
+//  - Search with highlighted matches
 
 //! A basic table view implementation for [cursive](https://crates.io/crates/cursive).
 #![deny(
@@ -21,6 +22,7 @@
 extern crate cursive;
 
 // STD Dependencies -----------------------------------------------------------
+use crate::views::highlight;
 use std::cmp::{self, Ordering};
 use std::collections::HashMap;
 use std::hash::Hash;
@@ -50,6 +52,12 @@ where
     fn cmp(&self, other: &Self, column: H) -> Ordering
     where
         Self: Sized;
+
+    /// Method returning the color of the item for the specified column
+    /// from type `H`; `None` falls back to the color of the column.
+    fn to_column_color(&self, _column: H) -> Option<theme::ColorStyle> {
+        None
+    }
 }
 
 /// Callback used when a column is sorted.
@@ -131,6 +139,7 @@ pub struct TableView<T: TableViewItem<H>, H: Eq + Hash + Copy + Clone + 'static>
     focus: usize,
     items: Vec<T>,
     rows_to_items: Vec<usize>,
+    highlight: Option<String>,
 
     on_sort: Option<OnSortCallback<H>>,
     // TODO Pass drawing offsets into the handlers so a popup menu
@@ -166,6 +175,7 @@ impl<T: TableViewItem<H>, H: Eq + Hash + Copy + Clone + 'static> TableView<T, H>
             focus: 0,
             items: Vec::new(),
             rows_to_items: Vec::new(),
+            highlight: None,
 
             on_sort: None,
             on_submit: None,
@@ -390,6 +400,11 @@ impl<T: TableViewItem<H>, H: Eq + Hash + Copy + Clone + 'static> TableView<T, H>
         self.with(|t| t.set_on_select(cb))
     }
 
+    /// Highlights all occurrences of the given pattern.
+    pub fn set_highlight(&mut self, pattern: Option<String>) {
+        self.highlight = pattern;
+    }
+
     /// Removes all items from this view.
     pub fn clear(&mut self) {
         self.items.clear();
@@ -480,6 +495,13 @@ impl<T: TableViewItem<H>, H: Eq + Hash + Copy + Clone + 'static> TableView<T, H>
         &mut self.items
     }
 
+    /// Returns a immmutable reference to the item shown in the specified row.
+    pub fn borrow_item_at_row(&self, row_index: usize) -> Option<&T> {
+        self.rows_to_items
+            .get(row_index)
+            .and_then(|index| self.items.get(*index))
+    }
+
     /// Returns the index of the currently selected item within the underlying
     /// storage vector.
     pub fn item(&self) -> Option<usize> {
@@ -579,7 +601,7 @@ impl<T: TableViewItem<H>, H: Eq + Hash + Copy + Clone + 'static> TableView<T, H>
         callback: C,
     ) {
         let mut column_offset = 0;
-        for (_index, column) in self.columns.iter().enumerate() {
+        for column in self.columns.iter() {
             let printer = &printer.offset((column_offset, 0)).focused(true);
 
             callback(printer, column);
@@ -609,8 +631,10 @@ impl<T: TableViewItem<H>, H: Eq + Hash + Copy + Clone + 'static> TableView<T, H>
 
     fn draw_item(&self, focused: bool, printer: &Printer, i: usize) {
         self.draw_columns(printer, " ", |printer, column| {
-            let value = self.items[self.rows_to_items[i]].to_column(column.column);
-            column.draw_row(focused, printer, value.as_str());
+            let item = &self.items[self.rows_to_items[i]];
+            let value = item.to_column(column.column);
+            let color = item.to_column_color(column.column);
+            column.draw_row(focused, printer, value.as_str(), color, &self.highlight);
         });
     }
 
@@ -680,8 +704,23 @@ impl<T: TableViewItem<H> + 'static, H: Eq + Hash + Copy + Clone + 'static> View
     for TableView<T, H>
 {
     fn draw(&self, printer: &Printer) {
-        //        let printer = &printer.offset((0, 2)).focused(true);
-        let printer = &printer.focused(true);
+        self.draw_columns(printer, " ", |printer, column| {
+            let color = if column.order != Ordering::Equal || column.selected {
+                if self.column_select && column.selected && self.enabled {
+                    theme::ColorStyle::highlight()
+                } else {
+                    theme::ColorStyle::highlight_inactive()
+                }
+            } else {
+                theme::ColorStyle::primary()
+            };
+
+            printer.with_color(color, |printer| {
+                column.draw_header(printer);
+            });
+        });
+
+        let printer = &printer.offset((0, 1)).focused(true);
         self.scrollbase.draw(printer, |printer, i| {
             if i < self.items.len() {
                 self.draw_item(self.focus == i, printer, i);
@@ -730,7 +769,8 @@ impl<T: TableViewItem<H> + 'static, H: Eq + Hash + Copy + Clone + 'static> View
             column.width = (remaining_width as f32 / remaining_columns as f32).floor() as usize;
         }
 
-        self.scrollbase.set_heights(size.y, item_count);
+        self.scrollbase
+            .set_heights(size.y.saturating_sub(1), item_count);
         self.last_size = size;
     }
 
@@ -934,7 +974,14 @@ impl<H: Copy + Clone + 'static> TableColumn<H> {
         printer.print((0, 0), header.as_str());
     }
 
-    fn draw_row(&self, focused: bool, printer: &Printer, value: &str) {
+    fn draw_row(
+        &self,
+        focused: bool,
+        printer: &Printer,
+        value: &str,
+        color: Option<theme::ColorStyle>,
+        highlight: &Option<String>,
+    ) {
         let value = match self.alignment {
             HAlign::Left => format!("{:<width$.N$} ", value, width = self.width, N = self.width),
             HAlign::Right => format!("{:>width$.N$} ", value, width = self.width, N = self.width),
@@ -944,12 +991,15 @@ impl<H: Copy + Clone + 'static> TableColumn<H> {
             if focused {
                 theme::ColorStyle::highlight()
             } else {
-                self.color
+                color.unwrap_or(self.color)
             },
             |printer| {
                 printer.print((0, 0), value.as_str());
             },
         );
+        if let Some(pattern) = highlight {
+            highlight::draw_matches(printer, &value, pattern);
+        }
     }
 }
 
@@ -1021,6 +1071,27 @@ mod tests {
         assert!(simple_table.len() == 11);
     }
 
+    #[test]
+    fn should_sort_by_column() {
+        let mut simple_table = setup_test_table();
+        simple_table.set_items(
+            ["b", "c", "a"]
+                .iter()
+                .map(|name| SimpleItem {
+                    name: name.to_string(),
+                })
+                .collect(),
+        );
+
+        simple_table.sort_by(SimpleColumn::Name, Ordering::Greater);
+        simple_table.set_selected_row(0);
+        assert_eq!(simple_table.item(), Some(1));
+
+        simple_table.sort_by(SimpleColumn::Name, Ordering::Less);
+        simple_table.set_selected_row(0);
+        assert_eq!(simple_table.item(), Some(2));
+    }
+
     #[test]
     fn should_insert_into_empty_table() {
         let mut simple_table = setup_test_table();